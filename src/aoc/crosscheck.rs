@@ -0,0 +1,116 @@
+use std::fmt::{Display, Formatter};
+
+use crate::aoc::{Context, Day, get_day, get_examples, get_generator};
+use crate::aoc::runner::{Part, SolveError};
+use crate::aoc::tools::prop::{check, Config, Failure};
+
+type Solver<'a> = Box<dyn Fn(&Context) -> Result<String, SolveError> + 'a>;
+
+/// Another way to solve a part, e.g. a brute force kept as an oracle for the optimised main solver.
+pub struct Alternative<'a> {
+    pub part: Part,
+    pub name: &'static str,
+    solve: Solver<'a>,
+}
+
+impl<'a> Alternative<'a> {
+    pub fn new(part: Part, name: &'static str, solve: impl Fn(&Context) -> Result<String, SolveError> + 'a) -> Self {
+        Alternative { part, name, solve: Box::new(solve) }
+    }
+
+    pub fn solve(&self, ctx: &Context) -> Result<String, SolveError> {
+        (self.solve)(ctx)
+    }
+}
+
+/// An alternative that does not agree with the main solver of its part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disagreement {
+    pub part: Part,
+    pub alternative: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl Display for Disagreement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Part {} by {} is {} instead of {}", self.part, self.alternative, self.actual, self.expected)
+    }
+}
+
+/// Runs every alternative of a day and compares it with the main solver of its part.
+pub fn crosscheck(solver: &dyn Day, ctx: &Context) -> Result<Vec<Disagreement>, SolveError> {
+    let mut disagreements = Vec::new();
+    for alternative in solver.alternatives() {
+        let expected = match alternative.part {
+            Part::One => solver.part1_with(ctx)?,
+            Part::Two => solver.part2_with(ctx)?,
+        };
+        let actual = alternative.solve(ctx)?;
+        if actual != expected {
+            disagreements.push(Disagreement { part: alternative.part, alternative: alternative.name, expected, actual });
+        }
+    }
+    Ok(disagreements)
+}
+
+/// Cross-checks a day on the examples of its puzzle description.
+/// Returns the disagreements together with the number of the example, counted from 1.
+pub fn crosscheck_examples(day: u8, ctx: &Context) -> Result<Vec<(usize, Disagreement)>, SolveError> {
+    let mut disagreements = Vec::new();
+    for (i, example) in get_examples(day).into_iter().enumerate() {
+        let solver = get_day(day, example.to_string()).unwrap();
+        disagreements.extend(crosscheck(solver.as_ref(), ctx)?.into_iter().map(|d| (i + 1, d)));
+    }
+    Ok(disagreements)
+}
+
+/// Cross-checks a day on generated inputs. A failure holds the smallest input found on which the solvers disagree.
+/// Panics if the day is not implemented yet.
+pub fn crosscheck_generated(day: u8, config: &Config) -> Result<usize, Failure<String>> {
+    let generate = get_generator(day).unwrap_or_else(|| panic!("Day {} not implemented yet", day));
+    check(config, generate, |input| {
+        let solver = get_day(day, input.clone()).unwrap();
+        let disagreements = crosscheck(solver.as_ref(), &Context::default()).map_err(|e| e.to_string())?;
+        if disagreements.is_empty() {
+            Ok(())
+        } else {
+            Err(disagreements.iter().map(|d| d.to_string()).collect::<Vec<String>>().join(", "))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Counter;
+
+    impl Day for Counter {
+        fn part1(&self) -> String {
+            "1".to_string()
+        }
+
+        fn alternatives(&self) -> Vec<Alternative<'_>> {
+            vec![
+                Alternative::new(Part::One, "agreeing", |_| Ok("1".to_string())),
+                Alternative::new(Part::One, "off by one", |_| Ok("2".to_string())),
+                Alternative::new(Part::Two, "cancelled", |ctx| {
+                    ctx.cancel.check()?;
+                    Ok(String::new())
+                }),
+            ]
+        }
+    }
+
+    #[test]
+    fn test_crosscheck() {
+        let disagreements = crosscheck(&Counter, &Context::default()).unwrap();
+        assert_eq!(disagreements.len(), 1);
+        assert_eq!(disagreements[0].to_string(), "Part 1 by off by one is 2 instead of 1");
+
+        let ctx = Context::default();
+        ctx.cancel.cancel();
+        assert_eq!(crosscheck(&Counter, &ctx), Err(SolveError::Cancelled));
+    }
+}
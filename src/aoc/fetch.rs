@@ -0,0 +1,42 @@
+use std::fs::File;
+
+use log::{debug, info};
+
+/// Given a day between 1 and 24, download the AoC input file for that day if it not already exists.
+/// Requires a session cookie to be passed as an environment variable.
+pub fn get_day_input(day: u8) -> Result<(), Box<dyn std::error::Error>> {
+    let url = format!("https://adventofcode.com/2023/day/{}/input", day);
+    let output_folder = format!("input/day{}.txt", day);
+    if !std::path::Path::new("input").exists() {
+        std::fs::create_dir("input")?;
+    }
+    if std::path::Path::new(&output_folder).exists() {
+        debug!("Input for day {} already exists at {}", day, output_folder);
+        return Ok(());
+    }
+    info!("Downloading input for day {} to {}", day, output_folder);
+    let session_cookie = read_session_cookie()?;
+    download_file(&url, &session_cookie, &output_folder)?;
+    Ok(())
+}
+
+/// Downloads a text file via http and writes its content to a given output folder.
+/// Authentication is passed via the session cookie.
+pub fn download_file(url: &str, session: &str, output_folder: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let session_cookie = format!("session={}", session);
+    let client = reqwest::blocking::Client::new();
+    let mut resp = client.get(url)
+        .header(reqwest::header::COOKIE, session_cookie)
+        .send()?;
+    debug!("Download of {} responded with {}", url, resp.status());
+    let mut out = File::create(output_folder)?;
+    std::io::copy(&mut resp, &mut out)?;
+    Ok(())
+}
+
+/// Reads the session cookie from a .env file and returns it as a string.
+/// The .env file should be in the same folder as the Cargo.toml file.
+pub fn read_session_cookie() -> Result<String, Box<dyn std::error::Error>> {
+    let session_cookie = dotenv::var("SESSION_COOKIE")?;
+    Ok(session_cookie)
+}
@@ -0,0 +1,132 @@
+use std::fs;
+use std::io;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::aoc::{get_day, get_generator};
+use crate::aoc::progress::ProgressMode;
+use crate::aoc::runner::{panic_message, Part, run_part, Status};
+use crate::aoc::tools::prop::{check, Config, Failure, Gen};
+
+/// Text that often trips up parsers: the separators of the puzzle formats, numbers that overflow and odd characters.
+const TOKENS: [&str; 17] = ["\n", "\n\n", " ", "-", "0", "99999999999999999999", "S", ":", "|", ",", ";", "(", ")",
+    "=", "map:", "é", "\0"];
+
+/// Inputs up to this many bytes are solved as well, larger ones are only parsed to keep the fuzzer fast.
+const SOLVE_LIMIT: usize = 1000;
+
+/// Solving a small input takes far less, a part that runs longer does not stop at all.
+const SOLVE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Reads every file of a corpus directory, sorted by name so runs are reproducible.
+pub fn load_corpus(dir: &Path) -> io::Result<Vec<Vec<u8>>> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    paths.sort();
+    paths.iter().filter(|p| p.is_file()).map(fs::read).collect()
+}
+
+/// Mostly printable ASCII, as most formats are, but any byte now and then.
+fn byte(g: &mut Gen) -> u8 {
+    if g.chance(90) {
+        g.range(0x20..=0x7e) as u8
+    } else {
+        g.below(256) as u8
+    }
+}
+
+/// Applies random edits to the bytes, more of them for larger sizes. No edits at all is the simplest case.
+pub fn mutate(g: &mut Gen, bytes: &[u8]) -> Vec<u8> {
+    let mut bytes = bytes.to_vec();
+    for _ in 0..g.usize(0..=g.size()) {
+        let at = g.usize(0..=bytes.len());
+        match g.below(6) {
+            0 if at < bytes.len() => {
+                bytes.remove(at);
+            }
+            1 => bytes.insert(at, byte(g)),
+            2 if at < bytes.len() => bytes[at] = byte(g),
+            3 => {
+                let token = g.choose(&TOKENS).unwrap();
+                bytes.splice(at..at, token.bytes());
+            }
+            4 => {
+                let copy = bytes[at..g.usize(at..=bytes.len())].to_vec();
+                let to = g.usize(0..=bytes.len());
+                bytes.splice(to..to, copy);
+            }
+            5 => bytes.truncate(at),
+            _ => {}
+        }
+    }
+    bytes
+}
+
+/// A mutated corpus entry, or a mutated generated input to reach past the examples.
+fn input(g: &mut Gen, day: u8, corpus: &[Vec<u8>]) -> String {
+    let seed = match get_generator(day) {
+        Some(generate) if corpus.is_empty() || g.chance(25) => generate(g).into_bytes(),
+        _ => g.choose(corpus).cloned().unwrap_or_default(),
+    };
+    String::from_utf8_lossy(&mutate(g, &seed)).into_owned()
+}
+
+/// The entry point for a single input: the parse step of the day has to return a value or an error pointing
+/// into the input. Both parts of a small input that parses have to stop without panicking, though they may
+/// report that the input has no answer. Invalid UTF-8 is replaced, as the inputs are read as text.
+/// Panics if the day is not implemented yet.
+pub fn fuzz_target(day: u8, data: &[u8]) -> Result<(), String> {
+    let input = String::from_utf8_lossy(data).into_owned();
+    let solver = get_day(day, input.clone()).unwrap_or_else(|| panic!("Day {} not implemented yet", day));
+    match catch_unwind(AssertUnwindSafe(|| solver.validate())) {
+        Ok(Ok(())) if input.len() > SOLVE_LIMIT => Ok(()),
+        Ok(Ok(())) => [Part::One, Part::Two].into_iter()
+            .try_for_each(|part| match run_part(solver.clone(), part, Some(SOLVE_TIMEOUT), ProgressMode::Hidden) {
+                Status::Panicked(message) => Err(format!("Part {} panicked: {}", part, message)),
+                Status::TimedOut(_) => Err(format!("Part {} did not stop", part)),
+                _ => Ok(()),
+            }),
+        Ok(Err(error)) => {
+            let columns = input.split('\n').nth(error.line.wrapping_sub(1)).map(|line| line.chars().count() + 1);
+            match columns {
+                Some(columns) if (1..=columns).contains(&error.column) => Ok(()),
+                _ => Err(format!("Error outside the input at {}", error)),
+            }
+        }
+        Err(panic) => Err(format!("Panicked: {}", panic_message(panic))),
+    }
+}
+
+/// Feeds mutated corpus entries and generated inputs to the parse step of a day and solves the small ones.
+/// A failure holds the smallest input found on which a step panics, does not stop or points outside the input.
+/// Panics if the day is not implemented yet.
+pub fn fuzz(day: u8, corpus: &[Vec<u8>], config: &Config) -> Result<usize, Failure<String>> {
+    check(config, |g| input(g, day, corpus), |input| fuzz_target(day, input.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mutate() {
+        let mut g = Gen::new(3, 0);
+        assert_eq!(mutate(&mut g, b"abc"), b"abc");
+        let mut g = Gen::new(3, 50);
+        assert_ne!(mutate(&mut g, b"abc"), b"abc");
+    }
+
+    #[test]
+    fn test_fuzz_target() {
+        assert_eq!(fuzz_target(7, b"32T3K 765"), Ok(()));
+        assert_eq!(fuzz_target(7, b"32T3X 765"), Ok(()));
+        assert_eq!(fuzz_target(7, &[0xff, b'\n', 0xfe]), Ok(()));
+        // Inputs without an answer are fine as long as the parts say so
+        assert_eq!(fuzz_target(8, b"L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)"), Ok(()));
+        assert_eq!(fuzz_target(10, b"...\n.S-"), Ok(()));
+        let winners = format!("Card 1: 1 | {}", "1 ".repeat(70));
+        assert_eq!(fuzz_target(4, winners.as_bytes()), Ok(()));
+    }
+}
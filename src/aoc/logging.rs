@@ -0,0 +1,135 @@
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Prefix of all log targets within this crate, stripped before matching module filters.
+const CRATE_PREFIX: &str = concat!(env!("CARGO_CRATE_NAME"), "::");
+
+/// A minimal logger writing to stderr, so log output never mixes with the answers on stdout.
+/// The default level can be overridden per module, e.g. `day8=trace` or `aoc::runner=debug`.
+pub struct Logger {
+    default: LevelFilter,
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl Logger {
+    pub fn new(default: LevelFilter) -> Self {
+        Logger { default, modules: Vec::new() }
+    }
+
+    /// Parses a comma separated list of `module=level` directives.
+    /// A directive without a module sets the default level instead.
+    pub fn parse_filters(mut self, filters: &str) -> Result<Self, String> {
+        for directive in filters.split(',').map(|d| d.trim()).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((module, level)) => {
+                    let level = level.parse::<LevelFilter>()
+                        .map_err(|_| format!("Invalid log level '{}' for module '{}'", level, module))?;
+                    self.modules.push((module.trim().to_string(), level));
+                }
+                None => {
+                    self.default = directive.parse::<LevelFilter>()
+                        .map_err(|_| format!("Invalid log level '{}'", directive))?;
+                }
+            }
+        }
+        Ok(self)
+    }
+
+    /// Installs the logger as the global logger of the `log` facade.
+    pub fn init(self) -> Result<(), log::SetLoggerError> {
+        log::set_max_level(self.max_level());
+        log::set_boxed_logger(Box::new(self))
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.modules.iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, |acc, level| acc.max(level))
+    }
+
+    /// Finds the level for a log target, the most specific module filter wins.
+    fn level_for(&self, target: &str) -> LevelFilter {
+        let target = target.strip_prefix(CRATE_PREFIX).unwrap_or(target);
+        self.modules.iter()
+            .filter(|(module, _)| target == module
+                || target.strip_prefix(module.as_str()).is_some_and(|rest| rest.starts_with("::")))
+            .max_by_key(|(module, _)| module.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let target = record.target();
+        eprintln!("[{:<5} {}] {}",
+                  record.level(),
+                  target.strip_prefix(CRATE_PREFIX).unwrap_or(target),
+                  record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+/// Maps the number of `-v` and `-q` flags to a default level, starting from warnings.
+pub fn verbosity(verbose: u8, quiet: u8) -> LevelFilter {
+    let levels = [
+        LevelFilter::Off,
+        LevelFilter::Error,
+        LevelFilter::Warn,
+        LevelFilter::Info,
+        LevelFilter::Debug,
+        LevelFilter::Trace,
+    ];
+    let index = (2 + verbose as i32 - quiet as i32).clamp(0, levels.len() as i32 - 1);
+    levels[index as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity() {
+        assert_eq!(verbosity(0, 0), LevelFilter::Warn);
+        assert_eq!(verbosity(2, 0), LevelFilter::Debug);
+        assert_eq!(verbosity(9, 0), LevelFilter::Trace);
+        assert_eq!(verbosity(0, 1), LevelFilter::Error);
+        assert_eq!(verbosity(0, 5), LevelFilter::Off);
+    }
+
+    #[test]
+    fn test_module_filters() {
+        let logger = Logger::new(LevelFilter::Warn)
+            .parse_filters("day8=trace, aoc=info,aoc::runner=debug")
+            .unwrap();
+
+        assert_eq!(logger.level_for(&format!("{}day8", CRATE_PREFIX)), LevelFilter::Trace);
+        assert_eq!(logger.level_for(&format!("{}day5", CRATE_PREFIX)), LevelFilter::Warn);
+        assert_eq!(logger.level_for(&format!("{}day80", CRATE_PREFIX)), LevelFilter::Warn);
+        assert_eq!(logger.level_for(&format!("{}aoc::runner", CRATE_PREFIX)), LevelFilter::Debug);
+        assert_eq!(logger.level_for(&format!("{}aoc::progress", CRATE_PREFIX)), LevelFilter::Info);
+        assert_eq!(logger.max_level(), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn test_later_directive_wins() {
+        let logger = Logger::new(LevelFilter::Warn)
+            .parse_filters("aoc::progress=info,aoc::progress=off")
+            .unwrap();
+        assert_eq!(logger.level_for(&format!("{}aoc::progress", CRATE_PREFIX)), LevelFilter::Off);
+    }
+
+    #[test]
+    fn test_default_directive() {
+        let logger = Logger::new(LevelFilter::Warn).parse_filters("debug").unwrap();
+        assert_eq!(logger.level_for("anything"), LevelFilter::Debug);
+        assert!(Logger::new(LevelFilter::Warn).parse_filters("day8=loud").is_err());
+    }
+}
@@ -1,13 +1,49 @@
-use std::fs::File;
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::{day1, day10, day11, day2, day3, day4, day5, day6, day7, day8, day9};
+use crate::aoc::cancel::CancellationToken;
+use crate::aoc::crosscheck::Alternative;
+use crate::aoc::progress::{Progress, ProgressMode};
+use crate::aoc::runner::{Part, run_part, SolveError, Status};
+use crate::aoc::tools::parse::ParseError;
+use crate::aoc::tools::prop::Gen;
+use crate::aoc::viz::Canvas;
+use crate::aoc::viz::record::Recorder;
 
-pub(crate) mod tools;
+pub mod cancel;
+pub mod crosscheck;
+#[cfg(feature = "fetch")]
+pub mod fetch;
+pub mod fuzz;
+pub mod logging;
+pub mod progress;
+pub mod runner;
+pub mod tools;
+pub mod viz;
 
-pub trait Day {
+/// State handed to a solver by the runner.
+#[derive(Clone, Default)]
+pub struct Context {
+    /// Polled by long-running solvers to stop early once the runner gives up on them
+    pub cancel: CancellationToken,
+    /// Reports how far a solver got, rendered by the CLI
+    pub progress: Progress,
+}
+
+impl Context {
+    pub fn new(cancel: CancellationToken, progress: Progress) -> Self {
+        Context { cancel, progress }
+    }
+}
+
+pub trait Day: Send + Sync {
+    /// Solves both parts in the calling thread, without a timeout or cancellation.
+    #[deprecated(note = "use `runner::run_part` to run each part in a supervised worker")]
     fn run(&self) -> (String, String) {
         (self.part1(), self.part2())
     }
+
     fn part1(&self) -> String {
         "".to_string()
     }
@@ -15,22 +51,35 @@ pub trait Day {
     fn part2(&self) -> String {
         "".to_string()
     }
-}
 
-/// Given a day between 1 and 24, download the AoC input file for that day if it not already exists.
-/// Requires a session cookie to be passed as an environment variable.
-pub fn get_day_input(day: u8) -> Result<(), Box<dyn std::error::Error>> {
-    let url = format!("https://adventofcode.com/2023/day/{}/input", day);
-    let output_folder = format!("input/day{}.txt", day);
-    if !std::path::Path::new("input").exists() {
-        std::fs::create_dir("input")?;
+    /// Runs part 1 within a runner context.
+    /// Solvers that can run for a long time override this and poll `ctx.cancel`.
+    fn part1_with(&self, _ctx: &Context) -> Result<String, SolveError> {
+        Ok(self.part1())
+    }
+
+    /// Runs part 2 within a runner context, see `part1_with`.
+    fn part2_with(&self, _ctx: &Context) -> Result<String, SolveError> {
+        Ok(self.part2())
     }
-    if std::path::Path::new(&output_folder).exists() {
-        return Ok(());
+
+    /// Draws the puzzle for `run --visualize` and `--export`, `None` for days without a visualisation.
+    fn visualize(&self) -> Option<Canvas> {
+        None
+    }
+
+    /// Records the frames of an animation for `run --frames`. Days without an animation record nothing.
+    fn animate(&self, _recorder: &mut Recorder) {}
+
+    /// Other solvers for the parts, which `crosscheck` compares with the main ones.
+    fn alternatives(&self) -> Vec<Alternative<'_>> {
+        Vec::new()
+    }
+
+    /// Runs only the parse step, failing with the position of the first malformed part instead of panicking.
+    fn validate(&self) -> Result<(), ParseError> {
+        Ok(())
     }
-    let session_cookie = read_session_cookie()?;
-    download_file(&url, &session_cookie, &output_folder)?;
-    Ok(())
 }
 
 /// Reads the input file for a given day and returns its content as a string.
@@ -40,58 +89,107 @@ pub fn read_input(day: u8) -> Result<String, Box<dyn std::error::Error>> {
     Ok(input)
 }
 
-/// Runs the solution for a given day.
-/// The solution is a function that takes a string as input and returns a string as output.
-pub fn run_solution(day: u8) -> Result<(String, String), Box<dyn std::error::Error>> {
-    get_day_input(day)?; // download input file if it does not exist yet
-    let input = read_input(day).unwrap();
-
-    let solution = match day {
-        1 => day1::Day1::new(input).run(),
-        2 => day2::Day2::new(input).run(),
-        3 => day3::Day3::new(input).run(),
-        4 => day4::Day4::new(input).run(),
-        5 => day5::Day5::new(input).run(),
-        6 => day6::Day6::new(input).run(),
-        7 => day7::Day7::new(input).run(),
-        8 => day8::Day8::new(input).run(),
-        9 => day9::Day9::new(input).run(),
-        10 => day10::Day10::new(input).run(),
-        11 => day11::Day11::new(input).run(),
-        // 12 => day12::Day12::new(input).run(),
-        // 13 => day13::Day13::new(input).run(),
-        // 14 => day14::Day14::new(input).run(),
-        // 15 => day15::Day15::new(input).run(),
-        // 16 => day16::Day16::new(input).run(),
-        // 17 => day17::Day17::new(input).run(),
-        // 18 => day18::Day18::new(input).run(),
-        // 19 => day19::Day19::new(input).run(),
-        // 20 => day20::Day20::new(input).run(),
-        // 21 => day21::Day21::new(input).run(),
-        // 22 => day22::Day22::new(input).run(),
-        // 23 => day23::Day23::new(input).run(),
-        // 24 => day24::Day24::new(input).run(),
-        _ => panic!("Day {} not implemented yet", day)
+/// Creates the solver for a given day from its input, `None` if the day is not implemented yet.
+pub fn get_day(day: u8, input: String) -> Option<Arc<dyn Day>> {
+    let solver: Arc<dyn Day> = match day {
+        1 => Arc::new(day1::Day1::new(input)),
+        2 => Arc::new(day2::Day2::new(input)),
+        3 => Arc::new(day3::Day3::new(input)),
+        4 => Arc::new(day4::Day4::new(input)),
+        5 => Arc::new(day5::Day5::new(input)),
+        6 => Arc::new(day6::Day6::new(input)),
+        7 => Arc::new(day7::Day7::new(input)),
+        8 => Arc::new(day8::Day8::new(input)),
+        9 => Arc::new(day9::Day9::new(input)),
+        10 => Arc::new(day10::Day10::new(input)),
+        11 => Arc::new(day11::Day11::new(input)),
+        // 12 => Arc::new(day12::Day12::new(input)),
+        // 13 => Arc::new(day13::Day13::new(input)),
+        // 14 => Arc::new(day14::Day14::new(input)),
+        // 15 => Arc::new(day15::Day15::new(input)),
+        // 16 => Arc::new(day16::Day16::new(input)),
+        // 17 => Arc::new(day17::Day17::new(input)),
+        // 18 => Arc::new(day18::Day18::new(input)),
+        // 19 => Arc::new(day19::Day19::new(input)),
+        // 20 => Arc::new(day20::Day20::new(input)),
+        // 21 => Arc::new(day21::Day21::new(input)),
+        // 22 => Arc::new(day22::Day22::new(input)),
+        // 23 => Arc::new(day23::Day23::new(input)),
+        // 24 => Arc::new(day24::Day24::new(input)),
+        _ => return None,
     };
-    Ok(solution)
+    Some(solver)
 }
 
-/// Downloads a text file via http and writes its content to a given output folder.
-/// Authentication is passed via the session cookie.
-fn download_file(url: &str, session: &str, output_folder: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let session_cookie = format!("session={}", session);
-    let client = reqwest::blocking::Client::new();
-    let mut resp = client.get(url)
-        .header(reqwest::header::COOKIE, session_cookie)
-        .send()?;
-    let mut out = File::create(output_folder)?;
-    std::io::copy(&mut resp, &mut out)?;
-    Ok(())
+/// The random input generator of a given day, `None` if the day is not implemented yet.
+pub fn get_generator(day: u8) -> Option<fn(&mut Gen) -> String> {
+    let generator: fn(&mut Gen) -> String = match day {
+        1 => day1::Day1::generate,
+        2 => day2::Day2::generate,
+        3 => day3::Day3::generate,
+        4 => day4::Day4::generate,
+        5 => day5::Day5::generate,
+        6 => day6::Day6::generate,
+        7 => day7::Day7::generate,
+        8 => day8::Day8::generate,
+        9 => day9::Day9::generate,
+        10 => day10::Day10::generate,
+        11 => day11::Day11::generate,
+        _ => return None,
+    };
+    Some(generator)
+}
+
+/// Includes the numbered example files of a day from the fuzzing corpus.
+macro_rules! examples {
+    ($day:literal, $($example:literal),+) => {
+        vec![$(include_str!(concat!("../../fuzz/corpus/day", $day, "/example", $example, ".txt"))),+]
+    };
+}
+
+/// The examples from the puzzle description of a given day, empty if the day is not implemented yet.
+pub fn get_examples(day: u8) -> Vec<&'static str> {
+    match day {
+        1 => examples!(1, 1, 2),
+        2 => examples!(2, 1),
+        3 => examples!(3, 1),
+        4 => examples!(4, 1),
+        5 => examples!(5, 1),
+        6 => examples!(6, 1),
+        7 => examples!(7, 1),
+        8 => examples!(8, 1, 2, 3),
+        9 => examples!(9, 1),
+        10 => examples!(10, 1, 2, 3, 4, 5),
+        11 => examples!(11, 1),
+        _ => Vec::new(),
+    }
+}
+
+/// Runs the solution for a given day.
+/// Each part is executed in a supervised worker, an optional timeout applies to each part separately.
+/// Without the `fetch` feature the input file has to exist already.
+pub fn run_solution(day: u8, timeout: Option<Duration>, progress: ProgressMode) -> Result<(Status, Status), Box<dyn std::error::Error>> {
+    #[cfg(feature = "fetch")]
+    fetch::get_day_input(day)?; // download input file if it does not exist yet
+    let input = read_input(day)?;
+
+    let solver = get_day(day, input).ok_or(format!("Day {} not implemented yet", day))?;
+    let part1 = run_part(solver.clone(), Part::One, timeout, progress);
+    let part2 = run_part(solver, Part::Two, timeout, progress);
+    Ok((part1, part2))
+}
+
+/// Draws the puzzle of a given day from its input file, `None` if the day has no visualisation.
+pub fn visualize(day: u8) -> Result<Option<Canvas>, Box<dyn std::error::Error>> {
+    let input = read_input(day)?;
+    let solver = get_day(day, input).ok_or(format!("Day {} not implemented yet", day))?;
+    Ok(solver.visualize())
 }
 
-/// Reads the session cookie from a .env file and returns it as a string.
-/// The .env file should be in the same folder as the Cargo.toml file.
-fn read_session_cookie() -> Result<String, Box<dyn std::error::Error>> {
-    let session_cookie = dotenv::var("SESSION_COOKIE")?;
-    Ok(session_cookie)
+/// Records the animation of a given day from its input file.
+pub fn animate(day: u8, recorder: &mut Recorder) -> Result<(), Box<dyn std::error::Error>> {
+    let input = read_input(day)?;
+    let solver = get_day(day, input).ok_or(format!("Day {} not implemented yet", day))?;
+    solver.animate(recorder);
+    Ok(())
 }
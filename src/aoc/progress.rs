@@ -0,0 +1,236 @@
+use std::io::{IsTerminal, Write};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use log::info;
+
+const BAR_WIDTH: usize = 30;
+const BAR_INTERVAL: Duration = Duration::from_millis(100);
+const LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Module filter of the progress log lines, which are logged at info level.
+pub const LOG_TARGET: &str = "aoc::progress";
+
+/// How progress updates are presented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    /// A single progress bar line on stderr, redrawn in place
+    Bar,
+    /// Periodic info log lines, for pipes and CI logs
+    Log,
+    /// No output at all, e.g. for timing runs
+    Hidden,
+}
+
+impl ProgressMode {
+    /// Uses a bar when stderr is a terminal and log lines otherwise.
+    pub fn detect() -> Self {
+        if std::io::stderr().is_terminal() {
+            ProgressMode::Bar
+        } else {
+            ProgressMode::Log
+        }
+    }
+}
+
+struct State {
+    label: String,
+    mode: ProgressMode,
+    total: AtomicU64,
+    position: AtomicU64,
+    message: Mutex<String>,
+    last_render: Mutex<Instant>,
+    /// Set by `finish`, a detached worker may keep reporting after its part timed out
+    finished: AtomicBool,
+    /// Width of the last bar line written, to clear it completely
+    width: AtomicUsize,
+}
+
+/// Progress handle passed to solvers through the `Context`.
+/// Cheap to clone and safe to share between worker threads. A disabled handle ignores all updates.
+#[derive(Clone, Default)]
+pub struct Progress {
+    state: Option<Arc<State>>,
+}
+
+impl Progress {
+    pub fn new(label: &str, mode: ProgressMode) -> Self {
+        if mode == ProgressMode::Hidden {
+            return Progress::disabled();
+        }
+        Progress {
+            state: Some(Arc::new(State {
+                label: label.to_string(),
+                mode,
+                total: AtomicU64::new(0),
+                position: AtomicU64::new(0),
+                message: Mutex::new(String::new()),
+                last_render: Mutex::new(Instant::now()),
+                finished: AtomicBool::new(false),
+                width: AtomicUsize::new(0),
+            }))
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Progress { state: None }
+    }
+
+    /// Sets the number of steps expected in total. Zero means unknown.
+    pub fn set_total(&self, total: u64) {
+        if let Some(state) = &self.state {
+            state.total.store(total, Ordering::Relaxed);
+            self.render(false);
+        }
+    }
+
+    pub fn inc(&self, delta: u64) {
+        if let Some(state) = &self.state {
+            state.position.fetch_add(delta, Ordering::Relaxed);
+            self.render(false);
+        }
+    }
+
+    pub fn set_message(&self, message: &str) {
+        if let Some(state) = &self.state {
+            *state.message.lock().unwrap() = message.to_string();
+            self.render(true);
+        }
+    }
+
+    /// Clears the progress bar, leaving the terminal ready for regular output.
+    /// Later updates are ignored.
+    pub fn finish(&self) {
+        if let Some(state) = &self.state {
+            // Wait for a render in progress, so it cannot draw after the bar is cleared
+            let _last_render = state.last_render.lock().unwrap();
+            if state.finished.swap(true, Ordering::Relaxed) {
+                return;
+            }
+            if state.mode == ProgressMode::Bar {
+                eprint!("\r{}\r", " ".repeat(state.width.load(Ordering::Relaxed)));
+                let _ = std::io::stderr().flush();
+            }
+        }
+    }
+
+    /// Redraws the progress, rate limited unless forced.
+    fn render(&self, force: bool) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        let interval = match state.mode {
+            ProgressMode::Bar => BAR_INTERVAL,
+            _ => LOG_INTERVAL,
+        };
+
+        // Another thread is rendering right now, skip this update
+        let Ok(mut last_render) = state.last_render.try_lock() else {
+            return;
+        };
+        if state.finished.load(Ordering::Relaxed) || (!force && last_render.elapsed() < interval) {
+            return;
+        }
+        *last_render = Instant::now();
+
+        let line = format_line(state);
+        match state.mode {
+            ProgressMode::Bar => {
+                // Pad to the previous width, a shorter message would leave the tail of the last one
+                let width = line.chars().count();
+                let previous = state.width.load(Ordering::Relaxed);
+                state.width.store(width.max(previous), Ordering::Relaxed);
+                eprint!("\r{}{}", line, " ".repeat(previous.saturating_sub(width)));
+                let _ = std::io::stderr().flush();
+            }
+            ProgressMode::Log => info!("{}", line),
+            ProgressMode::Hidden => (),
+        }
+    }
+}
+
+fn format_line(state: &State) -> String {
+    let position = state.position.load(Ordering::Relaxed);
+    let total = state.total.load(Ordering::Relaxed);
+    let message = state.message.lock().unwrap().clone();
+
+    if total == 0 {
+        return format!("{}: {} {}", state.label, position, message);
+    }
+
+    let ratio = (position as f64 / total as f64).min(1.0);
+    match state.mode {
+        ProgressMode::Bar => {
+            let filled = (ratio * BAR_WIDTH as f64) as usize;
+            format!("{}: [{}{}] {:>3}% {}",
+                    state.label,
+                    "#".repeat(filled),
+                    "-".repeat(BAR_WIDTH - filled),
+                    (ratio * 100.0) as u32,
+                    message)
+        }
+        _ => format!("{}: {:.1}% ({}/{}) {}", state.label, ratio * 100.0, position, total, message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(progress: &Progress) -> (u64, u64) {
+        match &progress.state {
+            Some(state) => (state.position.load(Ordering::Relaxed), state.total.load(Ordering::Relaxed)),
+            None => (0, 0),
+        }
+    }
+
+    #[test]
+    fn test_disabled_ignores_updates() {
+        let progress = Progress::disabled();
+        progress.set_total(10);
+        progress.inc(5);
+        assert_eq!(position(&progress), (0, 0));
+    }
+
+    #[test]
+    fn test_shared_between_threads() {
+        let progress = Progress::new("test", ProgressMode::Log);
+        progress.set_total(40);
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| (0..10).for_each(|_| progress.inc(1)));
+            }
+        });
+        assert_eq!(position(&progress), (40, 40));
+    }
+
+    #[test]
+    fn test_log_target() {
+        assert_eq!(module_path!(), format!("{}::{}::tests", env!("CARGO_CRATE_NAME"), LOG_TARGET));
+    }
+
+    #[test]
+    fn test_format_bar() {
+        let progress = Progress::new("Part 1", ProgressMode::Bar);
+        progress.set_total(4);
+        progress.inc(1);
+        let line = format_line(progress.state.as_ref().unwrap());
+        assert!(line.starts_with("Part 1: [#######-----"));
+        assert!(line.contains(" 25%"));
+    }
+
+    #[test]
+    fn test_finish_stops_rendering() {
+        let progress = Progress::new("Part 1", ProgressMode::Bar);
+        progress.set_message(&"x".repeat(200));
+        let state = progress.state.as_ref().unwrap();
+        assert!(state.width.load(Ordering::Relaxed) > 200);
+
+        progress.finish();
+        progress.set_message("after");
+        progress.finish();
+        assert!(state.finished.load(Ordering::Relaxed));
+        assert!(state.width.load(Ordering::Relaxed) > 200);
+    }
+}
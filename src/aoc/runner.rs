@@ -0,0 +1,219 @@
+use std::any::Any;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{debug, warn};
+
+use crate::aoc::{Context, Day};
+use crate::aoc::cancel::{CancellationToken, Cancelled};
+use crate::aoc::progress::{Progress, ProgressMode};
+use crate::aoc::tools::checked::Overflow;
+
+/// How long a cancelled worker gets to return before the runner stops waiting for it.
+const GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// Why a solver stopped without an answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    Cancelled,
+    /// The answer or an intermediate value does not fit the integer type of the solver
+    Overflow(Overflow),
+    /// The input parses, but lacks what the puzzle needs for an answer, like a start that never reaches the end
+    NoSolution(String),
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::Cancelled => write!(f, "{}", Cancelled),
+            SolveError::Overflow(overflow) => write!(f, "{}", overflow),
+            SolveError::NoSolution(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+impl From<Cancelled> for SolveError {
+    fn from(_: Cancelled) -> Self {
+        SolveError::Cancelled
+    }
+}
+
+impl From<Overflow> for SolveError {
+    fn from(overflow: Overflow) -> Self {
+        SolveError::Overflow(overflow)
+    }
+}
+
+/// The outcome of running a single part in a supervised worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Solved(String, Duration),
+    /// The part did not finish within the timeout. The worker was asked to cancel.
+    TimedOut(Duration),
+    /// The solver returned early because it observed a cancellation request.
+    Cancelled,
+    Overflowed(Overflow),
+    NoSolution(String),
+    Panicked(String),
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Solved(answer, elapsed) => write!(f, "{} ({:?})", answer, elapsed),
+            Status::TimedOut(timeout) => write!(f, "timed out after {:?}", timeout),
+            Status::Cancelled => write!(f, "cancelled"),
+            Status::Overflowed(overflow) => write!(f, "overflowed: {}", overflow),
+            Status::NoSolution(reason) => write!(f, "no solution: {}", reason),
+            Status::Panicked(message) => write!(f, "panicked: {}", message),
+        }
+    }
+}
+
+/// Runs one part of a solution on a worker thread.
+/// If a timeout is given and exceeded, the worker's cancellation token is triggered and
+/// `Status::TimedOut` is returned instead of blocking until the solver finishes.
+pub fn run_part(day: Arc<dyn Day>, part: Part, timeout: Option<Duration>, progress: ProgressMode) -> Status {
+    let job = move |ctx: &Context| match part {
+        Part::One => day.part1_with(ctx),
+        Part::Two => day.part2_with(ctx),
+    };
+    match supervise(&format!("Part {}", part), timeout, progress, job) {
+        Ok((answer, elapsed)) => Status::Solved(answer, elapsed),
+        Err(status) => status,
+    }
+}
+
+/// Runs a job on a worker thread like `run_part`, e.g. a cross-check of several solvers.
+/// Returns the output of the job with its duration, or the status explaining why there is none.
+pub fn supervise<T, F>(label: &str, timeout: Option<Duration>, progress: ProgressMode, job: F) -> Result<(T, Duration), Status>
+    where T: Send + 'static, F: FnOnce(&Context) -> Result<T, SolveError> + Send + 'static {
+    let token = CancellationToken::new();
+    let progress = Progress::new(label, progress);
+    let ctx = Context::new(token.clone(), progress.clone());
+    let (sender, receiver) = mpsc::channel();
+
+    debug!("Starting worker for {} with timeout {:?}", label, timeout);
+    let timer = Instant::now();
+    let worker = thread::spawn(move || {
+        let result = job(&ctx);
+        // The receiver is gone if the runner already gave up on this worker
+        let _ = sender.send(result);
+    });
+
+    let received = match timeout {
+        Some(timeout) => receiver.recv_timeout(timeout),
+        None => receiver.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+    };
+    progress.finish();
+
+    match received {
+        Ok(Ok(output)) => Ok((output, timer.elapsed())),
+        Ok(Err(SolveError::Cancelled)) => Err(Status::Cancelled),
+        Ok(Err(SolveError::Overflow(overflow))) => Err(Status::Overflowed(overflow)),
+        Ok(Err(SolveError::NoSolution(reason))) => Err(Status::NoSolution(reason)),
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            warn!("{} exceeded its timeout, cancelling", label);
+            token.cancel();
+            // Give the solver a chance to shut down cleanly, it is detached otherwise
+            match receiver.recv_timeout(GRACE_PERIOD) {
+                Ok(_) => debug!("Worker for {} shut down after cancellation", label),
+                Err(_) => warn!("Worker for {} did not stop within {:?}, detaching it", label, GRACE_PERIOD),
+            }
+            Err(Status::TimedOut(timeout.unwrap_or_default()))
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => match worker.join() {
+            Err(panic) => Err(Status::Panicked(panic_message(panic))),
+            Ok(()) => Err(Status::Panicked("worker exited without an answer".to_string())),
+        },
+    }
+}
+
+pub(crate) fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    if let Some(s) = panic.downcast_ref::<&str>() {
+        return s.to_string();
+    }
+    if let Some(s) = panic.downcast_ref::<String>() {
+        return s.clone();
+    }
+    "unknown panic".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aoc::tools::checked::Checked;
+
+    use super::*;
+
+    struct Slow;
+
+    impl Day for Slow {
+        fn part1(&self) -> String {
+            "fast".to_string()
+        }
+
+        fn part2_with(&self, ctx: &Context) -> Result<String, SolveError> {
+            loop {
+                ctx.cancel.check()?;
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+    }
+
+    struct Broken;
+
+    impl Day for Broken {
+        fn part1(&self) -> String {
+            panic!("broken solver")
+        }
+
+        fn part2_with(&self, _ctx: &Context) -> Result<String, SolveError> {
+            Ok(u32::MAX.try_add(1)?.to_string())
+        }
+    }
+
+    #[test]
+    fn test_solved() {
+        let status = run_part(Arc::new(Slow), Part::One, Some(Duration::from_secs(5)), ProgressMode::Hidden);
+        assert!(matches!(status, Status::Solved(ref s, _) if s == "fast"));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let timeout = Duration::from_millis(50);
+        let status = run_part(Arc::new(Slow), Part::Two, Some(timeout), ProgressMode::Hidden);
+        assert_eq!(status, Status::TimedOut(timeout));
+    }
+
+    #[test]
+    fn test_panic() {
+        let status = run_part(Arc::new(Broken), Part::One, None, ProgressMode::Hidden);
+        assert_eq!(status, Status::Panicked("broken solver".to_string()));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let status = run_part(Arc::new(Broken), Part::Two, None, ProgressMode::Hidden);
+        assert_eq!(status.to_string(), "overflowed: 4294967295 + 1 overflows u32");
+    }
+}
@@ -0,0 +1,334 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::iter::Sum;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+/// An arbitrary-precision integer, for answers that do not fit a machine word.
+/// Only supports what the puzzles need: addition, subtraction, multiplication and comparison.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    /// Little-endian base `2^32` digits without trailing zeros, empty for zero
+    digits: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> BigInt {
+        BigInt::default()
+    }
+
+    fn from_parts(negative: bool, mut digits: Vec<u32>) -> BigInt {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        // There is no negative zero
+        BigInt { negative: negative && !digits.is_empty(), digits }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt { negative: false, digits: self.digits.clone() }
+    }
+
+    pub fn pow(&self, mut exp: u32) -> BigInt {
+        let mut base = self.clone();
+        let mut result = BigInt::from(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            base = &base * &base;
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// The value as `i128`, `None` if it does not fit.
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.digits.len() > 4 {
+            return None;
+        }
+        let magnitude = self.digits.iter().rev().fold(0u128, |acc, d| acc << 32 | *d as u128);
+        if self.negative {
+            0i128.checked_sub_unsigned(magnitude)
+        } else {
+            i128::try_from(magnitude).ok()
+        }
+    }
+
+    /// The value as `i64`, `None` if it does not fit.
+    pub fn to_i64(&self) -> Option<i64> {
+        self.to_i128().and_then(|v| i64::try_from(v).ok())
+    }
+
+    fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+        a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+    }
+
+    fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+        let mut result = Vec::with_capacity(a.len().max(b.len()) + 1);
+        let mut carry = 0u64;
+        for i in 0..a.len().max(b.len()) {
+            let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+            result.push(sum as u32);
+            carry = sum >> 32;
+        }
+        result.push(carry as u32);
+        result
+    }
+
+    /// `a - b`, where `a` must not be smaller than `b`.
+    fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+        let mut result = Vec::with_capacity(a.len());
+        let mut borrow = 0i64;
+        for (i, d) in a.iter().enumerate() {
+            let mut diff = *d as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+            borrow = (diff < 0) as i64;
+            diff += borrow << 32;
+            result.push(diff as u32);
+        }
+        result
+    }
+
+    /// Divides the magnitude in place by a small divisor and returns the remainder.
+    fn div_rem_small(digits: &mut [u32], divisor: u32) -> u32 {
+        let mut remainder = 0u64;
+        for d in digits.iter_mut().rev() {
+            let current = remainder << 32 | *d as u64;
+            *d = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+        remainder as u32
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(n: i128) -> Self {
+        let mut magnitude = n.unsigned_abs();
+        let mut digits = Vec::new();
+        while magnitude > 0 {
+            digits.push(magnitude as u32);
+            magnitude >>= 32;
+        }
+        BigInt::from_parts(n < 0, digits)
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(n: i64) -> Self {
+        BigInt::from(n as i128)
+    }
+}
+
+impl From<u64> for BigInt {
+    fn from(n: u64) -> Self {
+        BigInt::from(n as i128)
+    }
+}
+
+impl From<i32> for BigInt {
+    fn from(n: i32) -> Self {
+        BigInt::from(n as i128)
+    }
+}
+
+impl From<u32> for BigInt {
+    fn from(n: u32) -> Self {
+        BigInt::from(n as i128)
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, BigInt::add_magnitude(&self.digits, &other.digits));
+        }
+        // Opposite signs subtract the smaller magnitude from the larger one, which decides the sign
+        match BigInt::cmp_magnitude(&self.digits, &other.digits) {
+            Ordering::Less => BigInt::from_parts(other.negative, BigInt::sub_magnitude(&other.digits, &self.digits)),
+            _ => BigInt::from_parts(self.negative, BigInt::sub_magnitude(&self.digits, &other.digits)),
+        }
+    }
+}
+
+impl Add for BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: BigInt) -> BigInt {
+        &self + &other
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.digits)
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        -self.clone()
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Sub for BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: BigInt) -> BigInt {
+        &self - &other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    /// Schoolbook multiplication, fine for the few hundred digits puzzles produce.
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut digits = vec![0u32; self.digits.len() + other.digits.len()];
+        for (i, a) in self.digits.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, b) in other.digits.iter().enumerate() {
+                let current = digits[i + j] as u64 + *a as u64 * *b as u64 + carry;
+                digits[i + j] = current as u32;
+                carry = current >> 32;
+            }
+            digits[i + other.digits.len()] = carry as u32;
+        }
+        BigInt::from_parts(self.negative != other.negative, digits)
+    }
+}
+
+impl Mul for BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: BigInt) -> BigInt {
+        &self * &other
+    }
+}
+
+impl Sum for BigInt {
+    fn sum<I: Iterator<Item=BigInt>>(iter: I) -> Self {
+        iter.fold(BigInt::zero(), |sum, v| sum + v)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => BigInt::cmp_magnitude(&self.digits, &other.digits),
+            (true, true) => BigInt::cmp_magnitude(&other.digits, &self.digits),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // Split off chunks of nine decimal digits, least significant first
+        const CHUNK: u32 = 1_000_000_000;
+        let mut digits = self.digits.clone();
+        let mut chunks = Vec::new();
+        while !digits.is_empty() {
+            chunks.push(BigInt::div_rem_small(&mut digits, CHUNK));
+            while digits.last() == Some(&0) {
+                digits.pop();
+            }
+        }
+        let mut text = chunks.pop().unwrap().to_string();
+        chunks.iter().rev().for_each(|c| text.push_str(&format!("{:09}", c)));
+        f.pad_integral(!self.negative, "", &text)
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, decimal) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if decimal.is_empty() || !decimal.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("Invalid integer '{}'", s));
+        }
+        let ten = BigInt::from(10);
+        let value = decimal.bytes()
+            .fold(BigInt::zero(), |acc, b| &(&acc * &ten) + &BigInt::from((b - b'0') as i32));
+        Ok(if negative { -value } else { value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = BigInt::from(i64::MAX);
+        assert_eq!((&a + &a).to_string(), "18446744073709551614");
+        assert_eq!((&a * &a).to_string(), "85070591730234615847396907784232501249");
+        assert_eq!(&BigInt::from(5) - &BigInt::from(8), BigInt::from(-3));
+        assert_eq!(&BigInt::from(-5) + &BigInt::from(5), BigInt::zero());
+        assert_eq!(&BigInt::from(-4) * &BigInt::from(6), BigInt::from(-24));
+        assert_eq!(BigInt::from(2).pow(100).to_string(), "1267650600228229401496703205376");
+        assert_eq!(&big("1267650600228229401496703205376") - &BigInt::from(2).pow(100), BigInt::zero());
+        assert_eq!([1, 2, 3].into_iter().map(BigInt::from).sum::<BigInt>(), BigInt::from(6));
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(BigInt::from(i128::MIN).to_i128(), Some(i128::MIN));
+        assert_eq!(BigInt::from(i128::MAX).to_i128(), Some(i128::MAX));
+        assert_eq!((BigInt::from(i128::MAX) + BigInt::from(1)).to_i128(), None);
+        assert_eq!(BigInt::from(-7).to_i64(), Some(-7));
+        assert_eq!(BigInt::from(i64::MAX as i128 + 1).to_i64(), None);
+    }
+
+    #[test]
+    fn test_order_and_display() {
+        assert!(BigInt::from(-10) < BigInt::from(-2));
+        assert!(BigInt::from(-2) < BigInt::zero());
+        assert!(big("100000000000000000000") > BigInt::from(i64::MAX));
+        assert_eq!(big("-1000000000000000000001").to_string(), "-1000000000000000000001");
+        assert_eq!(big("+0").to_string(), "0");
+        assert_eq!(format!("{:>5}", BigInt::from(-42)), "  -42");
+        assert!("12a".parse::<BigInt>().is_err());
+        assert!("-".parse::<BigInt>().is_err());
+    }
+}
@@ -0,0 +1,341 @@
+use std::fmt::{Debug, Formatter};
+
+use crate::aoc::tools::{Grid, Point};
+
+const BITS: usize = u64::BITS as usize;
+
+/// Iterates over the set bits of a slice of words, in ascending order.
+fn ones(words: &[u64]) -> impl Iterator<Item=usize> + '_ {
+    words.iter()
+        .enumerate()
+        .flat_map(|(i, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                // Clear the lowest set bit
+                word &= word - 1;
+                Some(i * BITS + bit)
+            })
+        })
+}
+
+/// A set of small integers below `64 * W`, stored inline so it is `Copy` and cheap to hash.
+/// Meant for search states, e.g. the set of keys collected so far.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FixedBitSet<const W: usize> {
+    words: [u64; W],
+}
+
+impl<const W: usize> Default for FixedBitSet<W> {
+    fn default() -> Self {
+        FixedBitSet { words: [0; W] }
+    }
+}
+
+impl<const W: usize> FixedBitSet<W> {
+    pub const CAPACITY: usize = W * BITS;
+
+    pub fn new() -> Self {
+        FixedBitSet::default()
+    }
+
+    /// Adds a value, false if it already was present. Panics if the value is not below `CAPACITY`.
+    pub fn insert(&mut self, value: usize) -> bool {
+        assert!(value < Self::CAPACITY, "{} does not fit into a bitset of {} bits", value, Self::CAPACITY);
+        let mask = 1 << (value % BITS);
+        let word = &mut self.words[value / BITS];
+        let added = *word & mask == 0;
+        *word |= mask;
+        added
+    }
+
+    /// Removes a value, false if it was not present.
+    pub fn remove(&mut self, value: usize) -> bool {
+        let present = self.contains(value);
+        if present {
+            self.words[value / BITS] &= !(1 << (value % BITS));
+        }
+        present
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        value < Self::CAPACITY && self.words[value / BITS] & (1 << (value % BITS)) != 0
+    }
+
+    /// Number of values in the set.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=usize> + '_ {
+        ones(&self.words)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        FixedBitSet { words: std::array::from_fn(|i| self.words[i] | other.words[i]) }
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        FixedBitSet { words: std::array::from_fn(|i| self.words[i] & other.words[i]) }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        FixedBitSet { words: std::array::from_fn(|i| self.words[i] & !other.words[i]) }
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.difference(other).is_empty()
+    }
+}
+
+impl<const W: usize> FromIterator<usize> for FixedBitSet<W> {
+    fn from_iter<T: IntoIterator<Item=usize>>(iter: T) -> Self {
+        let mut set = FixedBitSet::new();
+        iter.into_iter().for_each(|v| { set.insert(v); });
+        set
+    }
+}
+
+impl<const W: usize> Debug for FixedBitSet<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// A set of non-negative integers that grows as needed.
+/// Trailing empty words are dropped, so equal sets compare and hash equal regardless of their history.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        BitSet::default()
+    }
+
+    /// Preallocates room for values below `capacity`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        BitSet { words: Vec::with_capacity(capacity.div_ceil(BITS)) }
+    }
+
+    fn trim(&mut self) {
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
+
+    /// Adds a value, false if it already was present.
+    pub fn insert(&mut self, value: usize) -> bool {
+        let index = value / BITS;
+        if index >= self.words.len() {
+            self.words.resize(index + 1, 0);
+        }
+        let mask = 1 << (value % BITS);
+        let added = self.words[index] & mask == 0;
+        self.words[index] |= mask;
+        added
+    }
+
+    /// Removes a value, false if it was not present.
+    pub fn remove(&mut self, value: usize) -> bool {
+        let present = self.contains(value);
+        if present {
+            self.words[value / BITS] &= !(1 << (value % BITS));
+            self.trim();
+        }
+        present
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.words.get(value / BITS).is_some_and(|w| w & (1 << (value % BITS)) != 0)
+    }
+
+    /// Number of values in the set.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=usize> + '_ {
+        ones(&self.words)
+    }
+
+    pub fn union(&self, other: &BitSet) -> BitSet {
+        let (long, short) = if self.words.len() >= other.words.len() { (self, other) } else { (other, self) };
+        let mut words = long.words.clone();
+        words.iter_mut().zip(&short.words).for_each(|(w, o)| *w |= o);
+        BitSet { words }
+    }
+
+    pub fn intersection(&self, other: &BitSet) -> BitSet {
+        let mut set = BitSet { words: self.words.iter().zip(&other.words).map(|(a, b)| a & b).collect() };
+        set.trim();
+        set
+    }
+
+    pub fn difference(&self, other: &BitSet) -> BitSet {
+        let mut words = self.words.clone();
+        words.iter_mut().zip(&other.words).for_each(|(w, o)| *w &= !o);
+        let mut set = BitSet { words };
+        set.trim();
+        set
+    }
+
+    pub fn is_subset(&self, other: &BitSet) -> bool {
+        self.difference(other).is_empty()
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<T: IntoIterator<Item=usize>>(iter: T) -> Self {
+        let mut set = BitSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<usize> for BitSet {
+    fn extend<T: IntoIterator<Item=usize>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|v| { self.insert(v); });
+    }
+}
+
+impl Debug for BitSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// One bit per cell of a grid, for marking visited positions without hashing them.
+/// Points outside the grid are never contained and cannot be inserted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VisitedMask {
+    bits: BitSet,
+    width: usize,
+    height: usize,
+}
+
+impl VisitedMask {
+    pub fn new(width: usize, height: usize) -> Self {
+        VisitedMask { bits: BitSet::with_capacity(width * height), width, height }
+    }
+
+    /// An empty mask of the same size as the grid.
+    pub fn for_grid<T>(grid: &Grid<T>) -> Self {
+        VisitedMask::new(grid.width(), grid.height())
+    }
+
+    fn index(&self, p: Point) -> Option<usize> {
+        let (x, y) = p.to_index()?;
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+
+    /// Marks a point, false if it was marked before. Panics if the point lies outside the grid.
+    pub fn insert(&mut self, p: Point) -> bool {
+        let index = self.index(p)
+            .unwrap_or_else(|| panic!("{} is outside of the {}x{} mask", p, self.width, self.height));
+        self.bits.insert(index)
+    }
+
+    pub fn remove(&mut self, p: Point) -> bool {
+        self.index(p).is_some_and(|i| self.bits.remove(i))
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.index(p).is_some_and(|i| self.bits.contains(i))
+    }
+
+    /// Number of marked points.
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// The marked points, row by row.
+    pub fn iter(&self) -> impl Iterator<Item=Point> + '_ {
+        self.bits.iter().map(|i| Point::from((i % self.width, i / self.width)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_fixed_bitset() {
+        let mut set = FixedBitSet::<2>::new();
+        assert!(set.insert(3));
+        assert!(set.insert(127));
+        assert!(!set.insert(3));
+        assert!(set.contains(127));
+        assert!(!set.contains(128));
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![3, 127]);
+        assert!(set.remove(3));
+        assert!(!set.remove(3));
+
+        let a: FixedBitSet<1> = [1, 2, 3].into_iter().collect();
+        let b: FixedBitSet<1> = [2, 3, 4].into_iter().collect();
+        assert_eq!(a.union(&b).iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(a.intersection(&b).iter().collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(a.difference(&b).iter().collect::<Vec<_>>(), vec![1]);
+        assert!(a.intersection(&b).is_subset(&a));
+        assert_eq!(format!("{:?}", a), "{1, 2, 3}");
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit")]
+    fn test_fixed_bitset_overflow() {
+        FixedBitSet::<1>::new().insert(64);
+    }
+
+    #[test]
+    fn test_bitset() {
+        let mut a: BitSet = [1, 200, 64].into_iter().collect();
+        let b: BitSet = [64, 65].into_iter().collect();
+        assert_eq!(a.len(), 3);
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![1, 64, 200]);
+        assert_eq!(a.union(&b).iter().collect::<Vec<_>>(), vec![1, 64, 65, 200]);
+        assert_eq!(b.union(&a), a.union(&b));
+        assert_eq!(a.intersection(&b).iter().collect::<Vec<_>>(), vec![64]);
+        assert_eq!(a.difference(&b).iter().collect::<Vec<_>>(), vec![1, 200]);
+
+        // Sets with the same values are equal and hash the same, however they were built
+        a.remove(200);
+        let c: BitSet = [64, 1].into_iter().collect();
+        assert_eq!(a, c);
+        assert_eq!(HashSet::from([a, c]).len(), 1);
+        assert!(b.difference(&b).is_empty());
+    }
+
+    #[test]
+    fn test_visited_mask() {
+        let grid = Grid::parse("...\n...", Some).unwrap();
+        let mut mask = VisitedMask::for_grid(&grid);
+        assert!(mask.insert(Point::new(2, 1)));
+        assert!(mask.insert(Point::new(0, 1)));
+        assert!(!mask.insert(Point::new(2, 1)));
+        assert!(mask.contains(Point::new(0, 1)));
+        assert!(!mask.contains(Point::new(3, 0)));
+        assert!(!mask.contains(Point::new(-1, 0)));
+        assert_eq!(mask.iter().collect::<Vec<_>>(), vec![Point::new(0, 1), Point::new(2, 1)]);
+        assert!(mask.remove(Point::new(0, 1)));
+        assert_eq!(mask.len(), 1);
+    }
+}
@@ -0,0 +1,131 @@
+use std::any::type_name;
+use std::fmt::{Display, Formatter};
+
+use crate::aoc::tools::rational::Rational;
+
+/// An arithmetic operation whose result does not fit its type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow {
+    /// The operation with its operands, like `4294967295 + 1`
+    pub expression: String,
+    pub type_name: &'static str,
+}
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} overflows {}", self.expression, self.type_name)
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+/// Arithmetic that reports overflow as an error instead of wrapping or panicking.
+pub trait Checked: Copy + Display {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn try_add(self, other: Self) -> Result<Self, Overflow>;
+
+    fn try_sub(self, other: Self) -> Result<Self, Overflow>;
+
+    fn try_mul(self, other: Self) -> Result<Self, Overflow>;
+}
+
+fn overflow<T: Display>(a: T, op: &str, b: T) -> Overflow {
+    Overflow { expression: format!("{} {} {}", a, op, b), type_name: type_name::<T>() }
+}
+
+macro_rules! impl_checked {
+    ($($t:ty),*) => {
+        $(
+            impl Checked for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn try_add(self, other: Self) -> Result<Self, Overflow> {
+                    self.checked_add(other).ok_or_else(|| overflow(self, "+", other))
+                }
+
+                fn try_sub(self, other: Self) -> Result<Self, Overflow> {
+                    self.checked_sub(other).ok_or_else(|| overflow(self, "-", other))
+                }
+
+                fn try_mul(self, other: Self) -> Result<Self, Overflow> {
+                    self.checked_mul(other).ok_or_else(|| overflow(self, "*", other))
+                }
+            }
+        )*
+    };
+}
+
+impl_checked!(u32, u64, usize, i32, i64, i128);
+
+impl Checked for Rational {
+    const ZERO: Self = Rational::ZERO;
+    const ONE: Self = Rational::ONE;
+
+    fn try_add(self, other: Self) -> Result<Self, Overflow> {
+        self.checked_add(other).ok_or_else(|| overflow(self, "+", other))
+    }
+
+    fn try_sub(self, other: Self) -> Result<Self, Overflow> {
+        self.checked_sub(other).ok_or_else(|| overflow(self, "-", other))
+    }
+
+    fn try_mul(self, other: Self) -> Result<Self, Overflow> {
+        self.checked_mul(other).ok_or_else(|| overflow(self, "*", other))
+    }
+}
+
+/// Sums the values, stopping at the first overflow.
+pub fn checked_sum<T: Checked>(values: impl IntoIterator<Item=T>) -> Result<T, Overflow> {
+    values.into_iter().try_fold(T::ZERO, T::try_add)
+}
+
+/// Multiplies the values, stopping at the first overflow.
+pub fn checked_product<T: Checked>(values: impl IntoIterator<Item=T>) -> Result<T, Overflow> {
+    values.into_iter().try_fold(T::ONE, T::try_mul)
+}
+
+/// Sums the products of pairs, like bids times ranks.
+pub fn checked_dot<T: Checked>(pairs: impl IntoIterator<Item=(T, T)>) -> Result<T, Overflow> {
+    pairs.into_iter().try_fold(T::ZERO, |sum, (a, b)| sum.try_add(a.try_mul(b)?))
+}
+
+/// Converts between integer types, reporting values that do not fit as overflow.
+pub fn convert<T, U>(value: T) -> Result<U, Overflow>
+    where T: Copy + Display, U: TryFrom<T> {
+    U::try_from(value).map_err(|_| Overflow { expression: value.to_string(), type_name: type_name::<U>() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_ops() {
+        assert_eq!(3u32.try_add(4), Ok(7));
+        let error = u32::MAX.try_add(1).unwrap_err();
+        assert_eq!(error.to_string(), "4294967295 + 1 overflows u32");
+        assert!(0u64.try_sub(1).is_err());
+        assert!(i64::MIN.try_mul(-1).is_err());
+        assert_eq!(Rational::new(1, 2).try_mul(Rational::new(2, 3)), Ok(Rational::new(1, 3)));
+        assert!(Rational::from_integer(i128::MAX).try_add(Rational::ONE).is_err());
+    }
+
+    #[test]
+    fn test_folds() {
+        assert_eq!(checked_sum([1u32, 2, 3]), Ok(6));
+        assert!(checked_sum([u32::MAX, 1]).is_err());
+        assert_eq!(checked_product([2i64, 3, 7]), Ok(42));
+        assert!(checked_product([i64::MAX, 2]).is_err());
+        assert_eq!(checked_dot([(1u32, 765), (2, 220)]), Ok(1205));
+        assert!(checked_dot([(65536u32, 65536)]).is_err());
+    }
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(convert::<usize, u32>(5), Ok(5u32));
+        assert_eq!(convert::<i64, u32>(-1).unwrap_err().to_string(), "-1 overflows u32");
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+
+use crate::aoc::tools::{Direction, Grid, Point};
+
+/// Disjoint sets over the elements `0..len`, with path compression and union by rank.
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    size: Vec<usize>,
+    sets: usize,
+}
+
+impl UnionFind {
+    /// Every element starts in a set of its own.
+    pub fn new(len: usize) -> UnionFind {
+        UnionFind {
+            parent: (0..len).collect(),
+            rank: vec![0; len],
+            size: vec![1; len],
+            sets: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Number of disjoint sets.
+    pub fn set_count(&self) -> usize {
+        self.sets
+    }
+
+    /// The representative of the set containing `x`.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // Point everything on the way directly to the root
+        let mut current = x;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    /// Merges the sets of `a` and `b`, false if they already were the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        // Hang the shallower tree below the deeper one
+        let (root, child) = if self.rank[a] < self.rank[b] { (b, a) } else { (a, b) };
+        self.parent[child] = root;
+        self.size[root] += self.size[child];
+        if self.rank[root] == self.rank[child] {
+            self.rank[root] += 1;
+        }
+        self.sets -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Size of the set containing `x`.
+    pub fn size_of(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// The members of every set, ordered by their smallest element.
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        let mut index = HashMap::new();
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for x in 0..self.len() {
+            let root = self.find(x);
+            let i = *index.entry(root).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[i].push(x);
+        }
+        groups
+    }
+}
+
+/// A labelling of elements into connected components.
+#[derive(Debug, Clone)]
+pub struct Components<K> {
+    /// The members of each component, the index is the component label
+    pub members: Vec<Vec<K>>,
+    labels: HashMap<K, usize>,
+}
+
+impl<K> Components<K>
+    where K: Clone + Eq + Hash {
+    fn new(members: Vec<Vec<K>>) -> Components<K> {
+        let labels = members.iter()
+            .enumerate()
+            .flat_map(|(label, m)| m.iter().map(move |k| (k.clone(), label)))
+            .collect();
+        Components { members, labels }
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// The component an element belongs to.
+    pub fn label(&self, k: &K) -> Option<usize> {
+        self.labels.get(k).copied()
+    }
+
+    pub fn sizes(&self) -> Vec<usize> {
+        self.members.iter().map(|m| m.len()).collect()
+    }
+
+    /// The first of the largest components.
+    pub fn largest(&self) -> Option<&Vec<K>> {
+        self.members.iter().reduce(|best, m| if m.len() > best.len() { m } else { best })
+    }
+}
+
+/// Labels the regions of a grid, orthogonal neighbours for which `connected` holds share a region.
+/// Every cell belongs to exactly one region, regions are ordered by their first cell row by row.
+pub fn grid_components<T>(grid: &Grid<T>, connected: impl Fn(&T, &T) -> bool) -> Components<Point> {
+    let index = |p: Point| p.y as usize * grid.width() + p.x as usize;
+    let mut sets = UnionFind::new(grid.width() * grid.height());
+    for (p, cell) in grid.iter() {
+        // Looking east and south is enough, the other directions are covered by the neighbours
+        for n in [p + Direction::E.vector(), p + Direction::S.vector()] {
+            if grid.get(n).is_some_and(|other| connected(cell, other)) {
+                sets.union(index(p), index(n));
+            }
+        }
+    }
+
+    let points: Vec<Point> = grid.iter().map(|(p, _)| p).collect();
+    Components::new(sets.groups().into_iter()
+        .map(|g| g.into_iter().map(|i| points[i]).collect())
+        .collect())
+}
+
+/// Labels the components of an undirected graph given as adjacency lists.
+/// Nodes only appearing as neighbours are included, components are ordered by their smallest node.
+pub fn graph_components<K>(adjacency: &HashMap<K, Vec<K>>) -> Components<K>
+    where K: Clone + Eq + Hash + Ord {
+    let nodes: Vec<K> = adjacency.iter()
+        .flat_map(|(k, neighbours)| neighbours.iter().chain([k]))
+        .cloned()
+        .collect::<BTreeSet<K>>()
+        .into_iter()
+        .collect();
+    let index: HashMap<&K, usize> = nodes.iter().enumerate().map(|(i, k)| (k, i)).collect();
+
+    let mut sets = UnionFind::new(nodes.len());
+    for (k, neighbours) in adjacency {
+        for n in neighbours {
+            sets.union(index[k], index[n]);
+        }
+    }
+
+    Components::new(sets.groups().into_iter()
+        .map(|g| g.into_iter().map(|i| nodes[i].clone()).collect())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_union_find() {
+        let mut sets = UnionFind::new(6);
+        assert_eq!(sets.set_count(), 6);
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(1, 3));
+        assert!(!sets.union(0, 2));
+        assert!(sets.connected(0, 3));
+        assert!(!sets.connected(0, 4));
+        assert_eq!(sets.size_of(2), 4);
+        assert_eq!(sets.set_count(), 3);
+        assert_eq!(sets.groups(), vec![vec![0, 1, 2, 3], vec![4], vec![5]]);
+    }
+
+    #[test]
+    fn test_grid_components() {
+        let grid = Grid::parse("AAB\nBAB\nBBA", Some).unwrap();
+        let regions = grid_components(&grid, |a, b| a == b);
+        assert_eq!(regions.sizes(), vec![3, 2, 3, 1]);
+        assert_eq!(regions.label(&Point::new(0, 1)), regions.label(&Point::new(1, 2)));
+        assert_ne!(regions.label(&Point::new(2, 0)), regions.label(&Point::new(0, 1)));
+        assert_eq!(regions.largest(), Some(&vec![Point::new(0, 0), Point::new(1, 0), Point::new(1, 1)]));
+    }
+
+    #[test]
+    fn test_graph_components() {
+        let graph = HashMap::from([
+            ("a", vec!["b"]),
+            ("c", vec!["b", "d"]),
+            ("e", vec![]),
+            ("f", vec!["g"]),
+        ]);
+        let components = graph_components(&graph);
+        assert_eq!(components.members, vec![vec!["a", "b", "c", "d"], vec!["e"], vec!["f", "g"]]);
+        assert_eq!(components.label(&"g"), Some(2));
+        assert_eq!(components.label(&"x"), None);
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+
+use crate::aoc::tools::math::crt;
+
+/// The shape of the sequence `start, step(start), step(step(start)), ...` of a finite state machine.
+/// After `tail` steps the sequence enters a loop of `length` states and repeats forever.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub tail: usize,
+    pub length: usize,
+    /// Steps before the loop at which the predicate holds
+    pub tail_hits: Vec<usize>,
+    /// Steps in `tail..tail + length` at which the predicate holds, it then holds every `length` steps
+    pub hits: Vec<usize>,
+}
+
+impl Cycle {
+    /// Evaluates the predicate on the first `tail + length` states.
+    fn scan<S>(start: S, mut step: impl FnMut(&S) -> S, mut predicate: impl FnMut(&S) -> bool,
+               tail: usize, length: usize) -> Cycle {
+        let mut state = start;
+        let mut hits = Vec::new();
+        for i in 0..tail + length {
+            if predicate(&state) {
+                hits.push(i);
+            }
+            state = step(&state);
+        }
+        Cycle::new(tail, length, hits)
+    }
+
+    fn new(tail: usize, length: usize, hits: Vec<usize>) -> Cycle {
+        let (tail_hits, hits) = hits.into_iter().partition(|h| *h < tail);
+        Cycle { tail, length, tail_hits, hits }
+    }
+
+    /// The step within `0..tail + length` that has the same state as the given step.
+    pub fn equivalent_step(&self, step: u64) -> usize {
+        let tail = self.tail as u64;
+        if step < tail {
+            step as usize
+        } else {
+            (tail + (step - tail) % self.length as u64) as usize
+        }
+    }
+
+    /// Whether the predicate holds for the state after the given number of steps.
+    pub fn holds_at(&self, step: u64) -> bool {
+        let step = self.equivalent_step(step);
+        self.tail_hits.contains(&step) || self.hits.contains(&step)
+    }
+}
+
+/// Floyd's tortoise and hare, constant memory but needs about three times as many steps as `hashed`.
+pub fn floyd<S>(start: S, mut step: impl FnMut(&S) -> S, predicate: impl FnMut(&S) -> bool) -> Cycle
+    where S: Clone + Eq {
+    // Find a state in the loop: the hare moves twice as fast and catches up
+    let mut tortoise = step(&start);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let next = step(&hare);
+        hare = step(&next);
+    }
+
+    // The hare is now a multiple of the loop length ahead, both meet at the start of the loop
+    let mut tail = 0;
+    tortoise = start.clone();
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        tail += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+
+    Cycle::scan(start, step, predicate, tail, length)
+}
+
+/// Brent's algorithm, constant memory and usually fewer steps than `floyd`.
+pub fn brent<S>(start: S, mut step: impl FnMut(&S) -> S, predicate: impl FnMut(&S) -> bool) -> Cycle
+    where S: Clone + Eq {
+    // Find the loop length by teleporting the tortoise to the hare at every power of two
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    // With the hare one loop length ahead, both meet at the start of the loop
+    let mut tail = 0;
+    tortoise = start.clone();
+    hare = start.clone();
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        tail += 1;
+    }
+
+    Cycle::scan(start, step, predicate, tail, length)
+}
+
+/// Remembers every state, the fewest steps but memory grows with `tail + length`.
+pub fn hashed<S>(start: S, mut step: impl FnMut(&S) -> S, mut predicate: impl FnMut(&S) -> bool) -> Cycle
+    where S: Clone + Eq + Hash {
+    let mut seen = HashMap::new();
+    let mut hits = Vec::new();
+    let mut state = start;
+    loop {
+        let i = seen.len();
+        if let Some(first) = seen.get(&state) {
+            return Cycle::new(*first, i - first, hits);
+        }
+        if predicate(&state) {
+            hits.push(i);
+        }
+        let next = step(&state);
+        seen.insert(state, i);
+        state = next;
+    }
+}
+
+/// The first step at which the predicate holds for all cycles at once, `None` if that never happens.
+/// The cycles are merged one at a time, keeping only the distinct solutions so far, so the work grows with
+/// the number of common hits within the lcm of the lengths instead of the number of combinations of hits.
+pub fn first_common_hit(cycles: &[Cycle]) -> Option<u64> {
+    let max_tail = cycles.iter().map(|c| c.tail).max().unwrap_or(0) as u64;
+    if let Some(step) = (0..max_tail).find(|s| cycles.iter().all(|c| c.holds_at(*s))) {
+        return Some(step);
+    }
+
+    // Past all tails every cycle only holds at its hits modulo its length
+    let mut solutions: BTreeSet<(i128, i128)> = BTreeSet::from([(0, 1)]);
+    for cycle in cycles {
+        solutions = solutions.iter()
+            .flat_map(|solution| cycle.hits.iter()
+                .filter_map(|h| crt([*solution, (*h as i128, cycle.length as i128)])))
+            .collect();
+    }
+
+    solutions.into_iter()
+        .map(|(step, modulus)| {
+            // Move the solution past the longest tail
+            let behind = (max_tail as i128 - step).max(0);
+            step + (behind + modulus - 1) / modulus * modulus
+        })
+        .min()
+        .map(|step| step as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0, 1, 2, then 3, 4, 5, 6 repeating
+    fn step(x: &u64) -> u64 {
+        if *x < 6 { x + 1 } else { 3 }
+    }
+
+    fn is_even(x: &u64) -> bool {
+        x.is_multiple_of(2)
+    }
+
+    #[test]
+    fn test_algorithms_agree() {
+        let expected = Cycle { tail: 3, length: 4, tail_hits: vec![0, 2], hits: vec![4, 6] };
+        assert_eq!(hashed(0, step, is_even), expected);
+        assert_eq!(floyd(0, step, is_even), expected);
+        assert_eq!(brent(0, step, is_even), expected);
+    }
+
+    #[test]
+    fn test_pure_loop() {
+        let step = |x: &u64| (x * x + 1) % 255;
+        let cycle = hashed(3, step, |_| false);
+        assert_eq!(floyd(3, step, |_| false), cycle);
+        assert_eq!(brent(3, step, |_| false), cycle);
+        assert_eq!(hashed(0, |x: &u64| (x + 1) % 5, |_| false), Cycle::new(0, 5, vec![]));
+    }
+
+    #[test]
+    fn test_holds_at() {
+        let cycle = hashed(0, step, is_even);
+        let expected = (0..3).chain((3..7).cycle()).take(100);
+        for (i, x) in expected.enumerate() {
+            assert_eq!(cycle.holds_at(i as u64), is_even(&x), "step {}", i);
+        }
+    }
+
+    #[test]
+    fn test_first_common_hit() {
+        let a = Cycle::new(0, 2, vec![1]);
+        let b = Cycle::new(0, 3, vec![2]);
+        assert_eq!(first_common_hit(&[a.clone(), b.clone()]), Some(5));
+        // Multiple hits per cycle, the earliest combination wins
+        let c = Cycle::new(0, 3, vec![1, 2]);
+        assert_eq!(first_common_hit(&[a.clone(), c]), Some(1));
+        // Shared tail hit before any loop starts
+        let d = Cycle::new(4, 2, vec![0, 1, 5]);
+        let e = Cycle::new(2, 6, vec![1, 2]);
+        assert_eq!(first_common_hit(&[d.clone(), e]), Some(1));
+        // Solution has to lie past the longest tail
+        let f = Cycle::new(7, 2, vec![7]);
+        assert_eq!(first_common_hit(&[f, Cycle::new(0, 1, vec![0])]), Some(7));
+        assert_eq!(first_common_hit(&[a, Cycle::new(0, 4, vec![2])]), None);
+        assert_eq!(first_common_hit(&[]), Some(0));
+    }
+
+    #[test]
+    fn test_first_common_hit_many_hits() {
+        // 10^20 combinations of hits, but only the multiples of 10 within 0..60 are common to all
+        let cycles: Vec<Cycle> = (0..20)
+            .map(|i| {
+                let length = [60, 30, 20, 12][i % 4];
+                Cycle::new(0, length, (0..length).filter(|h| h % [2, 5, 2, 1][i % 4] == 0).collect())
+            })
+            .collect();
+        assert_eq!(first_common_hit(&cycles), Some(0));
+        let shifted: Vec<Cycle> = cycles.into_iter()
+            .map(|c| Cycle::new(0, c.length, c.hits.into_iter().filter(|h| *h > 0).collect()))
+            .collect();
+        assert_eq!(first_common_hit(&shifted), Some(10));
+    }
+}
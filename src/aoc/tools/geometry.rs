@@ -0,0 +1,144 @@
+use crate::aoc::tools::Direction;
+use crate::aoc::tools::math::{gcd, Integer};
+
+/// Where a point lies relative to a polygon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// Builds the vertices of a polygon from a start and a list of straight runs,
+/// like "go 6 steps east, then 5 south". The polygon is closed implicitly.
+pub fn from_edges<T>(start: (T, T), edges: impl IntoIterator<Item=(Direction, T)>) -> Vec<(T, T)>
+    where T: Integer + From<i64> {
+    let mut vertices = vec![start];
+    let mut current = start;
+    for (direction, length) in edges {
+        let v = direction.vector();
+        current = (current.0 + T::from(v.x) * length, current.1 + T::from(v.y) * length);
+        vertices.push(current);
+    }
+    // Returning to the start would repeat the first vertex
+    if vertices.len() > 1 && vertices.last() == vertices.first() {
+        vertices.pop();
+    }
+    vertices
+}
+
+fn edges<T: Copy>(vertices: &[(T, T)]) -> impl Iterator<Item=((T, T), (T, T))> + '_ {
+    vertices.iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(a, b)| (*a, *b))
+}
+
+/// Twice the signed area by the shoelace formula, exact for integer vertices.
+/// Positive when the vertices run counter-clockwise with `y` pointing up, that is clockwise on screen.
+pub fn double_signed_area<T: Integer>(vertices: &[(T, T)]) -> T {
+    edges(vertices).fold(T::ZERO, |sum, ((x1, y1), (x2, y2))| sum + x1 * y2 - x2 * y1)
+}
+
+/// The enclosed area, rounded down if it is not a whole number.
+pub fn area<T: Integer>(vertices: &[(T, T)]) -> T {
+    double_signed_area(vertices).abs() / (T::ONE + T::ONE)
+}
+
+/// Number of lattice points on the edges, an edge from `(0, 0)` to `(4, 2)` passes through 2.
+pub fn boundary_points<T: Integer>(vertices: &[(T, T)]) -> T {
+    edges(vertices).fold(T::ZERO, |sum, ((x1, y1), (x2, y2))| sum + gcd(x2 - x1, y2 - y1))
+}
+
+/// Number of lattice points strictly inside, by Pick's theorem `A = I + B / 2 - 1`.
+/// The theorem needs a proper polygon, fewer than 3 vertices or collinear ones enclose nothing.
+pub fn interior_points<T: Integer>(vertices: &[(T, T)]) -> T {
+    let double_area = double_signed_area(vertices).abs();
+    if vertices.len() < 3 || double_area == T::ZERO {
+        return T::ZERO;
+    }
+    let two = T::ONE + T::ONE;
+    (double_area - boundary_points(vertices) + two) / two
+}
+
+/// Locates a point by casting a ray towards positive `x` and counting the edges it crosses.
+pub fn locate<T: Integer>(vertices: &[(T, T)], (px, py): (T, T)) -> Location {
+    let mut inside = false;
+    for ((x1, y1), (x2, y2)) in edges(vertices) {
+        let on_line = (x2 - x1) * (py - y1) == (y2 - y1) * (px - x1);
+        if on_line && x1.min(x2) <= px && px <= x1.max(x2) && y1.min(y2) <= py && py <= y1.max(y2) {
+            return Location::Boundary;
+        }
+        // Half-open in y so a ray through a vertex counts exactly one of its edges
+        if (y1 > py) != (y2 > py) {
+            // Whether the crossing lies right of the point, multiplied out to stay in integers
+            let lhs = (px - x1) * (y2 - y1);
+            let rhs = (py - y1) * (x2 - x1);
+            if (y2 > y1 && lhs < rhs) || (y2 < y1 && lhs > rhs) {
+                inside = !inside;
+            }
+        }
+    }
+    if inside { Location::Inside } else { Location::Outside }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aoc::tools::Direction::{E, N, S, W};
+
+    use super::*;
+
+    /// A 4x4 square with a 2x2 corner cut out
+    const L_SHAPE: [(i64, i64); 6] = [(0, 0), (2, 0), (2, 2), (4, 2), (4, 4), (0, 4)];
+
+    #[test]
+    fn test_area() {
+        assert_eq!(double_signed_area(&L_SHAPE), 24);
+        let reversed: Vec<_> = L_SHAPE.iter().rev().copied().collect();
+        assert_eq!(double_signed_area(&reversed), -24);
+        assert_eq!(area(&L_SHAPE), 12);
+        assert_eq!(area(&[(0, 0), (1, 0), (0, 1)]), 0);
+        assert_eq!(double_signed_area(&[(0i128, 0), (1, 0), (0, 1)]), 1);
+    }
+
+    #[test]
+    fn test_lattice_points() {
+        assert_eq!(boundary_points(&L_SHAPE), 16);
+        assert_eq!(interior_points(&L_SHAPE), 5);
+        assert_eq!(boundary_points(&[(0, 0), (4, 2), (0, 2)]), 2 + 4 + 2);
+        assert_eq!(interior_points(&[(0, 0), (4, 2), (0, 2)]), 1);
+    }
+
+    #[test]
+    fn test_degenerate_polygons() {
+        assert_eq!(interior_points::<i64>(&[]), 0);
+        assert_eq!(interior_points(&[(3, 4)]), 0);
+        assert_eq!(interior_points(&[(0, 0), (5, 0)]), 0);
+        assert_eq!(interior_points(&[(0, 0), (2, 0), (4, 0)]), 0);
+    }
+
+    #[test]
+    fn test_locate() {
+        assert_eq!(locate(&L_SHAPE, (0, 1)), Location::Boundary);
+        assert_eq!(locate(&L_SHAPE, (3, 2)), Location::Boundary);
+        assert_eq!(locate(&L_SHAPE, (3, 1)), Location::Outside);
+        assert_eq!(locate(&L_SHAPE, (5, 4)), Location::Outside);
+        assert_eq!(locate(&L_SHAPE, (1, 2)), Location::Inside);
+        // Rays at y = 2 pass through vertices
+        let inside: Vec<(i64, i64)> = (0..=6)
+            .flat_map(|y| (0..=6).map(move |x| (x, y)))
+            .filter(|p| locate(&L_SHAPE, *p) == Location::Inside)
+            .collect();
+        assert_eq!(inside.len() as i64, interior_points(&L_SHAPE));
+    }
+
+    #[test]
+    fn test_from_edges() {
+        // Example dig plan of 2023 day 18, the trench is 38 cubes long and digs out 62
+        let plan = [(E, 6), (S, 5), (W, 2), (S, 2), (E, 2), (S, 2), (W, 5),
+            (N, 2), (W, 1), (N, 2), (E, 2), (N, 3), (W, 2), (N, 2)];
+        let polygon = from_edges((0i128, 0), plan.iter().map(|(d, l)| (*d, *l as i128)));
+        assert_eq!(polygon.len(), 14);
+        assert_eq!(boundary_points(&polygon), 38);
+        assert_eq!(interior_points(&polygon) + boundary_points(&polygon), 62);
+    }
+}
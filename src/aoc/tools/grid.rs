@@ -0,0 +1,291 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
+
+use crate::aoc::tools::point::{Direction, Point};
+use crate::aoc::tools::read_lines;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    /// A row has a different length than the first one
+    Ragged { row: usize, expected: usize, found: usize },
+    /// The cell mapping rejected a character
+    InvalidChar { c: char, x: usize, y: usize },
+}
+
+impl Display for GridError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridError::Ragged { row, expected, found } =>
+                write!(f, "Row {} has {} cells, expected {}", row, found, expected),
+            GridError::InvalidChar { c, x, y } =>
+                write!(f, "Invalid character '{}' at ({}, {})", c, x, y),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// A rectangular 2D grid stored row by row in a single vector.
+/// Cells are addressed by a `Point` where `x` is the column and `y` the row, starting top left.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid from cells in row-major order.
+    /// Panics if the number of cells does not match the dimensions.
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Grid<T> {
+        assert_eq!(cells.len(), width * height, "Grid of {}x{} needs {} cells", width, height, width * height);
+        Grid { cells, width, height }
+    }
+
+    pub fn filled(width: usize, height: usize, value: T) -> Grid<T>
+        where T: Clone {
+        Grid::new(width, height, vec![value; width * height])
+    }
+
+    /// Creates a grid from a list of rows, which all need to have the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>, GridError> {
+        let width = rows.first().map(|r| r.len()).unwrap_or(0);
+        let height = rows.len();
+        let mut cells = Vec::with_capacity(width * height);
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(GridError::Ragged { row: y, expected: width, found: row.len() });
+            }
+            cells.extend(row);
+        }
+        Ok(Grid { cells, width, height })
+    }
+
+    /// Parses a grid from text, one row per line, mapping each character to a cell.
+    /// Lines are trimmed like in `read_lines`. The mapping returns `None` for invalid characters.
+    pub fn parse(input: &str, mut f: impl FnMut(char) -> Option<T>) -> Result<Grid<T>, GridError> {
+        let rows = read_lines(input).iter()
+            .enumerate()
+            .map(|(y, line)| line.chars()
+                .enumerate()
+                .map(|(x, c)| f(c).ok_or(GridError::InvalidChar { c, x, y }))
+                .collect::<Result<Vec<T>, GridError>>())
+            .collect::<Result<Vec<Vec<T>>, GridError>>()?;
+        Grid::from_rows(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, p: Point) -> bool {
+        self.cell_index(p).is_some()
+    }
+
+    /// Checked access, `None` if the point lies outside the grid.
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.cell_index(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.cell_index(p).map(|i| &mut self.cells[i])
+    }
+
+    fn cell_index(&self, p: Point) -> Option<usize> {
+        let (x, y) = p.to_index()?;
+        (x < self.width && y < self.height).then_some(y * self.width + x)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item=&[T]> {
+        // chunks panics on zero, an empty grid has no cells anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item=&T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1)).take(self.height)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item=impl Iterator<Item=&T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// Iterates over all cells together with their position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item=(Point, &T)> {
+        self.cells.iter()
+            .enumerate()
+            .map(|(i, c)| (Point::from((i % self.width, i / self.width)), c))
+    }
+
+    /// Positions of all cells matching the predicate, row by row.
+    pub fn find_all(&self, predicate: impl Fn(&T) -> bool) -> Vec<Point> {
+        self.iter()
+            .filter(|(_, c)| predicate(c))
+            .map(|(p, _)| p)
+            .collect()
+    }
+
+    /// The orthogonal neighbours of a position that lie within the grid, clockwise starting north.
+    pub fn neighbours4(&self, p: Point) -> impl Iterator<Item=Point> + '_ {
+        self.neighbours(p, &Direction::CARDINAL)
+    }
+
+    /// The orthogonal and diagonal neighbours of a position that lie within the grid.
+    pub fn neighbours8(&self, p: Point) -> impl Iterator<Item=Point> + '_ {
+        self.neighbours(p, &Direction::ALL)
+    }
+
+    fn neighbours(&self, p: Point, directions: &'static [Direction]) -> impl Iterator<Item=Point> + '_ {
+        directions.iter()
+            .map(move |d| p + d.vector())
+            .filter(|n| self.in_bounds(*n))
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    /// Builds a new grid of the given size, taking each cell from a position of this grid.
+    fn remap(&self, width: usize, height: usize, source: impl Fn(usize, usize) -> (usize, usize)) -> Grid<T>
+        where T: Clone {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| self[Point::from(source(x, y))].clone())
+            .collect();
+        Grid::new(width, height, cells)
+    }
+
+    /// Mirrors the grid along its main diagonal, rows become columns.
+    pub fn transpose(&self) -> Grid<T>
+        where T: Clone {
+        self.remap(self.height, self.width, |x, y| (y, x))
+    }
+
+    pub fn rotate_clockwise(&self) -> Grid<T>
+        where T: Clone {
+        let height = self.height;
+        self.remap(self.height, self.width, |x, y| (y, height - 1 - x))
+    }
+
+    pub fn rotate_counter_clockwise(&self) -> Grid<T>
+        where T: Clone {
+        let width = self.width;
+        self.remap(self.height, self.width, |x, y| (width - 1 - y, x))
+    }
+
+    /// Mirrors the grid left to right.
+    pub fn flip_horizontal(&self) -> Grid<T>
+        where T: Clone {
+        let width = self.width;
+        self.remap(self.width, self.height, |x, y| (width - 1 - x, y))
+    }
+
+    /// Mirrors the grid top to bottom.
+    pub fn flip_vertical(&self) -> Grid<T>
+        where T: Clone {
+        let height = self.height;
+        self.remap(self.width, self.height, |x, y| (x, height - 1 - y))
+    }
+}
+
+/// Unchecked access, panics if the point is out of bounds.
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point) -> &Self::Output {
+        self.get(p).unwrap_or_else(|| panic!("{} is outside of the {}x{} grid", p, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut Self::Output {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(p).unwrap_or_else(|| panic!("{} is outside of the {}x{} grid", p, width, height))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = r#"
+    abc
+    def
+    "#;
+
+    fn grid() -> Grid<char> {
+        Grid::parse(INPUT, Some).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = grid();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Point::new(2, 1)], 'f');
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(grid.get(Point::new(0, -1)), None);
+        assert_eq!(grid.to_string(), "abc\ndef");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Grid::parse("ab\nc", Some), Err(GridError::Ragged { row: 1, expected: 2, found: 1 }));
+        assert_eq!(Grid::parse("ab\ncx", |c| (c != 'x').then_some(c)),
+                   Err(GridError::InvalidChar { c: 'x', x: 1, y: 1 }));
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = grid();
+        assert_eq!(grid.row(1), &['d', 'e', 'f']);
+        assert_eq!(grid.column(1).collect::<String>(), "be");
+        assert_eq!(grid.columns().map(|c| c.collect::<String>()).collect::<Vec<_>>(), vec!["ad", "be", "cf"]);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = grid();
+        assert_eq!(grid.neighbours4(Point::new(0, 0)).collect::<Vec<_>>(), vec![Point::new(1, 0), Point::new(0, 1)]);
+        assert_eq!(grid.neighbours8(Point::new(1, 0)).count(), 5);
+        assert_eq!(grid.neighbours8(Point::new(1, 1)).collect::<Vec<_>>(),
+                   vec![Point::new(1, 0), Point::new(2, 0), Point::new(2, 1), Point::new(0, 1), Point::new(0, 0)]);
+    }
+
+    #[test]
+    fn test_transformations() {
+        let grid = grid();
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf");
+        assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc");
+        assert_eq!(grid.rotate_counter_clockwise().to_string(), "cf\nbe\nad");
+        assert_eq!(grid.flip_horizontal().to_string(), "cba\nfed");
+        assert_eq!(grid.flip_vertical().to_string(), "def\nabc");
+        assert_eq!(grid.rotate_clockwise().rotate_counter_clockwise(), grid);
+    }
+
+    #[test]
+    fn test_find_all() {
+        let grid = grid();
+        assert_eq!(grid.find_all(|c| "aei".contains(*c)), vec![Point::new(0, 0), Point::new(1, 1)]);
+    }
+}
@@ -0,0 +1,284 @@
+use std::cmp::{max, min};
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+/// A set of integers stored as sorted, disjoint and non-adjacent half-open intervals.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct IntervalSet {
+    intervals: Vec<Range<i64>>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        IntervalSet::default()
+    }
+
+    /// Builds a set from arbitrary ranges, merging overlapping and adjacent ones. Empty ranges are dropped.
+    pub fn from_ranges(ranges: impl IntoIterator<Item=Range<i64>>) -> Self {
+        let mut ranges: Vec<Range<i64>> = ranges.into_iter()
+            .filter(|r| !r.is_empty())
+            .collect();
+        ranges.sort_by_key(|r| r.start);
+
+        let mut intervals: Vec<Range<i64>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match intervals.last_mut() {
+                Some(last) if range.start <= last.end => last.end = max(last.end, range.end),
+                _ => intervals.push(range),
+            }
+        }
+        IntervalSet { intervals }
+    }
+
+    pub fn insert(&mut self, range: Range<i64>) {
+        *self = self.union(&IntervalSet::from(range));
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        // The first interval ending after the value is the only candidate
+        let i = self.intervals.partition_point(|r| r.end <= value);
+        self.intervals.get(i).is_some_and(|r| r.contains(&value))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Number of integers in the set.
+    pub fn total_len(&self) -> i64 {
+        self.intervals.iter().map(|r| r.end - r.start).sum()
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.intervals.first().map(|r| r.start)
+    }
+
+    pub fn max(&self) -> Option<i64> {
+        self.intervals.last().map(|r| r.end - 1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&Range<i64>> {
+        self.intervals.iter()
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        IntervalSet::from_ranges(self.intervals.iter().chain(other.intervals.iter()).cloned())
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a, b) = (&self.intervals[i], &other.intervals[j]);
+            let overlap = max(a.start, b.start)..min(a.end, b.end);
+            if !overlap.is_empty() {
+                intervals.push(overlap);
+            }
+            // Advance whichever interval ends first, the other may still overlap the next one
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { intervals }
+    }
+
+    /// All values of this set that are not in the other set.
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = Vec::new();
+        let mut j = 0;
+        for range in &self.intervals {
+            let mut start = range.start;
+            // Skip subtracted intervals that end before this range
+            while j < other.intervals.len() && other.intervals[j].end <= start {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.intervals.len() && other.intervals[k].start < range.end {
+                let cut = &other.intervals[k];
+                if cut.start > start {
+                    intervals.push(start..cut.start);
+                }
+                start = max(start, cut.end);
+                k += 1;
+            }
+            if start < range.end {
+                intervals.push(start..range.end);
+            }
+        }
+        IntervalSet { intervals }
+    }
+
+    /// Splits the intervals at the given boundaries, so that no returned piece contains a boundary
+    /// except at its start. The pieces are not merged again, hence returned as plain ranges.
+    pub fn split_at(&self, boundaries: &[i64]) -> Vec<Range<i64>> {
+        let mut boundaries = boundaries.to_vec();
+        boundaries.sort();
+        boundaries.dedup();
+
+        let mut pieces = Vec::new();
+        for range in &self.intervals {
+            let mut start = range.start;
+            for b in boundaries.iter().filter(|b| range.start < **b && **b < range.end) {
+                pieces.push(start..*b);
+                start = *b;
+            }
+            pieces.push(start..range.end);
+        }
+        pieces
+    }
+}
+
+impl From<Range<i64>> for IntervalSet {
+    fn from(range: Range<i64>) -> Self {
+        IntervalSet::from_ranges([range])
+    }
+}
+
+impl FromIterator<Range<i64>> for IntervalSet {
+    fn from_iter<T: IntoIterator<Item=Range<i64>>>(iter: T) -> Self {
+        IntervalSet::from_ranges(iter)
+    }
+}
+
+impl Display for IntervalSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let parts: Vec<String> = self.intervals.iter()
+            .map(|r| format!("[{}, {})", r.start, r.end))
+            .collect();
+        write!(f, "{{{}}}", parts.join(", "))
+    }
+}
+
+/// A rule of a piecewise map, shifting `source..source + len` to start at `dest`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OffsetRule {
+    pub source: i64,
+    pub dest: i64,
+    pub len: i64,
+}
+
+impl OffsetRule {
+    pub fn new(source: i64, dest: i64, len: i64) -> Self {
+        OffsetRule { source, dest, len }
+    }
+
+    pub fn source_range(&self) -> Range<i64> {
+        self.source..self.source + self.len
+    }
+
+    pub fn offset(&self) -> i64 {
+        self.dest - self.source
+    }
+}
+
+/// Maps integers by a list of offset rules. Values not covered by any rule map to themselves.
+/// Where rules overlap, the first matching rule applies.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct PiecewiseMap {
+    rules: Vec<OffsetRule>,
+}
+
+impl PiecewiseMap {
+    /// Creates a map from `(source, dest, len)` triples.
+    pub fn new(rules: impl IntoIterator<Item=(i64, i64, i64)>) -> Self {
+        PiecewiseMap {
+            rules: rules.into_iter()
+                .map(|(source, dest, len)| OffsetRule::new(source, dest, len))
+                .collect()
+        }
+    }
+
+    pub fn rules(&self) -> &[OffsetRule] {
+        &self.rules
+    }
+
+    pub fn map_value(&self, value: i64) -> i64 {
+        self.rules.iter()
+            .find(|r| r.source_range().contains(&value))
+            .map(|r| value + r.offset())
+            .unwrap_or(value)
+    }
+
+    /// Maps every value of the set at once by moving whole intervals instead of single values.
+    pub fn map_set(&self, set: &IntervalSet) -> IntervalSet {
+        let mut remaining = set.clone();
+        let mut mapped = Vec::new();
+        for rule in &self.rules {
+            let covered = remaining.intersection(&IntervalSet::from(rule.source_range()));
+            if covered.is_empty() {
+                continue;
+            }
+            mapped.extend(covered.iter().map(|r| r.start + rule.offset()..r.end + rule.offset()));
+            remaining = remaining.difference(&covered);
+        }
+        mapped.extend(remaining.iter().cloned());
+        IntervalSet::from_ranges(mapped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[Range<i64>]) -> IntervalSet {
+        IntervalSet::from_ranges(ranges.iter().cloned())
+    }
+
+    #[test]
+    fn test_merge() {
+        let s = set(&[5..8, 1..3, 2..4, 4..5, 10..10, 12..15]);
+        assert_eq!(s.iter().cloned().collect::<Vec<_>>(), vec![1..8, 12..15]);
+        assert_eq!(s.total_len(), 10);
+        assert_eq!((s.min(), s.max()), (Some(1), Some(14)));
+        assert!(s.contains(7));
+        assert!(!s.contains(8));
+        assert!(!s.contains(0));
+        assert_eq!(s.to_string(), "{[1, 8), [12, 15)}");
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[0..10, 20..30]);
+        let b = set(&[5..25, 28..40]);
+        assert_eq!(a.union(&b), IntervalSet::from(0..40));
+        assert_eq!(a.intersection(&b), set(&[5..10, 20..25, 28..30]));
+        assert_eq!(a.difference(&b), set(&[0..5, 25..28]));
+        assert_eq!(b.difference(&a), set(&[10..20, 30..40]));
+        assert_eq!(a.difference(&a), IntervalSet::new());
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut s = IntervalSet::new();
+        s.insert(3..5);
+        s.insert(0..1);
+        s.insert(1..3);
+        assert_eq!(s, IntervalSet::from(0..5));
+    }
+
+    #[test]
+    fn test_split_at() {
+        let s = set(&[0..10, 20..30]);
+        assert_eq!(s.split_at(&[25, 5, 10, 0, 5]), vec![0..5, 5..10, 20..25, 25..30]);
+    }
+
+    #[test]
+    fn test_piecewise_map() {
+        // seed-to-soil map of day 5
+        let map = PiecewiseMap::new([(98, 50, 2), (50, 52, 48)]);
+        assert_eq!(map.map_value(79), 81);
+        assert_eq!(map.map_value(99), 51);
+        assert_eq!(map.map_value(10), 10);
+
+        let seeds = set(&[79..93, 55..68, 95..102]);
+        let mapped = map.map_set(&seeds);
+        let expected: IntervalSet = seeds.iter()
+            .flat_map(|r| r.clone())
+            .map(|v| map.map_value(v)..map.map_value(v) + 1)
+            .collect();
+        assert_eq!(mapped, expected);
+        assert_eq!(mapped.total_len(), seeds.total_len());
+    }
+}
@@ -0,0 +1,254 @@
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+/// Signed integer types the number theory helpers work on.
+/// `mul_mod` is overflow-safe, use the `i128` implementation when intermediate values might not fit an `i64`.
+pub trait Integer: Copy + Ord + Debug
++ Add<Output=Self> + Sub<Output=Self> + Mul<Output=Self> + Div<Output=Self> + Rem<Output=Self> + Neg<Output=Self> {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs(self) -> Self;
+
+    fn rem_euclid(self, modulus: Self) -> Self;
+
+    /// `(self * other) mod modulus` without overflowing, the result lies in `0..modulus`.
+    fn mul_mod(self, other: Self, modulus: Self) -> Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_integer_widening {
+    ($t:ty, $wide:ty) => {
+        impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+
+            fn rem_euclid(self, modulus: Self) -> Self {
+                <$t>::rem_euclid(self, modulus)
+            }
+
+            fn mul_mod(self, other: Self, modulus: Self) -> Self {
+                (self as $wide * other as $wide).rem_euclid(modulus as $wide) as $t
+            }
+
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+
+            fn checked_mul(self, other: Self) -> Option<Self> {
+                <$t>::checked_mul(self, other)
+            }
+        }
+    };
+}
+
+impl_integer_widening!(i32, i64);
+impl_integer_widening!(i64, i128);
+
+impl Integer for i128 {
+    const ZERO: Self = 0;
+    const ONE: Self = 1;
+
+    fn abs(self) -> Self {
+        i128::abs(self)
+    }
+
+    fn rem_euclid(self, modulus: Self) -> Self {
+        i128::rem_euclid(self, modulus)
+    }
+
+    /// There is no wider type, so multiply by doubling and adding.
+    fn mul_mod(self, other: Self, modulus: Self) -> Self {
+        // Adds two values in 0..modulus without leaving that range
+        let add_mod = |a: i128, b: i128| if a >= modulus - b { a - (modulus - b) } else { a + b };
+
+        let mut a = self.rem_euclid(modulus);
+        let mut b = other.rem_euclid(modulus);
+        let mut result = 0;
+        while b > 0 {
+            if b & 1 == 1 {
+                result = add_mod(result, a);
+            }
+            a = add_mod(a, a);
+            b >>= 1;
+        }
+        result
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        i128::checked_add(self, other)
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        i128::checked_mul(self, other)
+    }
+}
+
+/// Greatest common divisor, always non-negative. `gcd(0, 0)` is 0.
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple, always non-negative. 0 if either value is 0.
+pub fn lcm<T: Integer>(a: T, b: T) -> T {
+    if a == T::ZERO || b == T::ZERO {
+        return T::ZERO;
+    }
+    (a / gcd(a, b) * b).abs()
+}
+
+/// Greatest common divisor of all values, 0 for no values.
+pub fn gcd_all<T: Integer>(values: impl IntoIterator<Item=T>) -> T {
+    values.into_iter().fold(T::ZERO, gcd)
+}
+
+/// Least common multiple of all values, 1 for no values.
+pub fn lcm_all<T: Integer>(values: impl IntoIterator<Item=T>) -> T {
+    values.into_iter().fold(T::ONE, lcm)
+}
+
+/// Returns `(g, x, y)` with `g = gcd(a, b)` and `a * x + b * y = g`.
+pub fn extended_gcd<T: Integer>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::ONE, T::ZERO);
+    let (mut old_y, mut y) = (T::ZERO, T::ONE);
+    while r != T::ZERO {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < T::ZERO {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The inverse of `a` modulo `m` in `0..m`, `None` if `a` and `m` are not coprime.
+pub fn mod_inverse<T: Integer>(a: T, m: T) -> Option<T> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    (g == T::ONE).then(|| x.rem_euclid(m))
+}
+
+/// `base ^ exp mod m` by repeated squaring, the result lies in `0..m`.
+pub fn mod_pow<T: Integer>(base: T, mut exp: u64, m: T) -> T {
+    let mut base = base.rem_euclid(m);
+    let mut result = T::ONE.rem_euclid(m);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.mul_mod(base, m);
+        }
+        base = base.mul_mod(base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Solves a system of congruences `x = residue (mod modulus)` with the Chinese Remainder Theorem.
+/// The moduli do not need to be coprime. Returns `(x, lcm of the moduli)` with `x` the smallest
+/// non-negative solution, or `None` if the congruences contradict each other, a modulus is not positive
+/// or the lcm of the moduli does not fit `T`.
+pub fn crt<T: Integer>(congruences: impl IntoIterator<Item=(T, T)>) -> Option<(T, T)> {
+    congruences.into_iter()
+        .try_fold((T::ZERO, T::ONE), |(r1, m1), (r2, m2)| {
+            if m2 <= T::ZERO {
+                return None;
+            }
+            // Both residues lie in 0..modulus, so their difference cannot overflow
+            let r2 = r2.rem_euclid(m2);
+            let (g, p, _) = extended_gcd(m1, m2);
+            let diff = r2 - r1;
+            if diff % g != T::ZERO {
+                return None;
+            }
+            // m1 * k = diff (mod m2), with p the inverse of m1 / g modulo m2 / g
+            let step = m2 / g;
+            let k = (diff / g).mul_mod(p, step);
+            let modulus = (m1 / g).checked_mul(m2)?;
+            Some((m1.checked_mul(k)?.checked_add(r1)?, modulus))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(-12, 18), 6);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(-4, 6), 12);
+        assert_eq!(lcm(0, 6), 0);
+        assert_eq!(gcd_all([24, 60, 36]), 12);
+        assert_eq!(lcm_all([2i64, 3, 4, 5]), 60);
+        assert_eq!(lcm_all(Vec::<i64>::new()), 1);
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        for (a, b) in [(240, 46), (46, 240), (-7, 3), (0, 5), (17, 0)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(g, gcd(a, b));
+            assert_eq!(a * x + b * y, g);
+        }
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mod_pow(4, 13, 497), 445);
+        assert_eq!(mod_pow(2, 0, 1), 0);
+        assert_eq!(mod_pow(3i64, 200, 1_000_000_007), mod_pow(9i64, 100, 1_000_000_007));
+        assert_eq!(mod_pow(-2, 3, 5), 2);
+    }
+
+    #[test]
+    fn test_mul_mod_no_overflow() {
+        let m = i64::MAX - 24;
+        assert_eq!((m - 1).mul_mod(m - 1, m), 1);
+        let m = i128::MAX - 158;
+        assert_eq!((m - 1).mul_mod(m - 1, m), 1);
+        assert_eq!((m - 2).mul_mod(2, m), m - 4);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // Moduli sharing a factor
+        assert_eq!(crt([(2, 4), (4, 6)]), Some((10, 12)));
+        assert_eq!(crt([(1, 4), (2, 6)]), None);
+        assert_eq!(crt(Vec::<(i64, i64)>::new()), Some((0, 1)));
+        // Solution beyond the range of i64
+        let big = crt([(1i128, 1_000_000_000_000_000_003), (2, 1_000_000_000_000_000_009)]).unwrap();
+        assert_eq!(big.1, 1_000_000_000_000_000_003 * 1_000_000_000_000_000_009);
+        assert_eq!(big.0 % 1_000_000_000_000_000_003, 1);
+        assert_eq!(big.0 % 1_000_000_000_000_000_009, 2);
+    }
+
+    #[test]
+    fn test_crt_invalid() {
+        assert_eq!(crt([(0, 0), (0, 0)]), None);
+        assert_eq!(crt([(1, 3), (2, 0)]), None);
+        assert_eq!(crt([(1, 3), (2, -5)]), None);
+        assert_eq!(crt([(-1, 3), (7, 5)]), Some((2, 15)));
+        // The lcm of the moduli does not fit an i64
+        assert_eq!(crt([(1i64, 1_000_000_007), (2, 1_000_000_009), (3, 1_000_000_021)]), None);
+        assert_eq!(crt([(i64::MAX - 1, i64::MAX), (i64::MIN, i64::MAX)]), Some((i64::MAX - 1, i64::MAX)));
+    }
+}
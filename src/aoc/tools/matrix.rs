@@ -0,0 +1,423 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Add, Div, Index, IndexMut, Mul, Neg, Sub};
+
+use crate::aoc::tools::GridError;
+use crate::aoc::tools::math::{mod_pow, Integer};
+use crate::aoc::tools::rational::Rational;
+
+/// Number types with exact division, which Gaussian elimination needs.
+pub trait Field: Copy + PartialEq + Debug
++ Add<Output=Self> + Sub<Output=Self> + Mul<Output=Self> + Div<Output=Self> + Neg<Output=Self> {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::ZERO
+    }
+}
+
+impl Field for Rational {
+    const ZERO: Self = Rational::ZERO;
+    const ONE: Self = Rational::ONE;
+}
+
+/// An integer modulo the prime `P`, always in `0..P`.
+/// `P` must be prime for division to work, and below `2^62` so sums do not overflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ModP<const P: i64>(i64);
+
+impl<const P: i64> ModP<P> {
+    pub fn new(value: i64) -> Self {
+        ModP(value.rem_euclid(P))
+    }
+
+    pub fn value(&self) -> i64 {
+        self.0
+    }
+
+    /// Panics for zero.
+    pub fn inverse(&self) -> Self {
+        assert_ne!(self.0, 0, "Zero has no inverse modulo {}", P);
+        // Fermat's little theorem
+        ModP(mod_pow(self.0, P as u64 - 2, P))
+    }
+}
+
+impl<const P: i64> From<i64> for ModP<P> {
+    fn from(value: i64) -> Self {
+        ModP::new(value)
+    }
+}
+
+impl<const P: i64> Add for ModP<P> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        ModP((self.0 + other.0) % P)
+    }
+}
+
+impl<const P: i64> Sub for ModP<P> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        ModP((self.0 - other.0).rem_euclid(P))
+    }
+}
+
+impl<const P: i64> Mul for ModP<P> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        ModP(self.0.mul_mod(other.0, P))
+    }
+}
+
+impl<const P: i64> Div for ModP<P> {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        ModP(self.0.mul_mod(other.inverse().0, P))
+    }
+}
+
+impl<const P: i64> Neg for ModP<P> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        ModP((P - self.0) % P)
+    }
+}
+
+impl<const P: i64> Field for ModP<P> {
+    const ZERO: Self = ModP(0);
+    const ONE: Self = ModP(1);
+}
+
+impl<const P: i64> Display for ModP<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The solutions of a linear system `A x = b`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Solution<T> {
+    None,
+    Unique(Vec<T>),
+    /// One solution with all free variables set to zero, and the indices of the free variables
+    Infinite { particular: Vec<T>, free: Vec<usize> },
+}
+
+/// The result of reducing a matrix to reduced row echelon form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Echelon<T> {
+    pub matrix: Matrix<T>,
+    /// The column of the pivot in each non-zero row
+    pub pivots: Vec<usize>,
+    /// Product of the pivots and row swaps, the determinant if the matrix was square and of full rank
+    determinant: T,
+}
+
+impl<T> Echelon<T> {
+    pub fn rank(&self) -> usize {
+        self.pivots.len()
+    }
+}
+
+/// A dense matrix stored row by row, indexed by `(row, column)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Matrix<T> {
+    cells: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Matrix<T> {
+    /// Creates a matrix from cells in row-major order.
+    /// Panics if the number of cells does not match the dimensions.
+    pub fn new(rows: usize, cols: usize, cells: Vec<T>) -> Matrix<T> {
+        assert_eq!(cells.len(), rows * cols, "Matrix of {}x{} needs {} cells", rows, cols, rows * cols);
+        Matrix { cells, rows, cols }
+    }
+
+    /// Creates a matrix from a list of rows, which all need to have the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Matrix<T>, GridError> {
+        let cols = rows.first().map(|r| r.len()).unwrap_or(0);
+        let count = rows.len();
+        let mut cells = Vec::with_capacity(cols * count);
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != cols {
+                return Err(GridError::Ragged { row: y, expected: cols, found: row.len() });
+            }
+            cells.extend(row);
+        }
+        Ok(Matrix { cells, rows: count, cols })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    pub fn row(&self, r: usize) -> &[T] {
+        &self.cells[r * self.cols..(r + 1) * self.cols]
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Matrix<U> {
+        Matrix { cells: self.cells.iter().map(f).collect(), rows: self.rows, cols: self.cols }
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for c in 0..self.cols {
+            self.cells.swap(a * self.cols + c, b * self.cols + c);
+        }
+    }
+}
+
+impl<T: Field> Matrix<T> {
+    pub fn zeros(rows: usize, cols: usize) -> Matrix<T> {
+        Matrix::new(rows, cols, vec![T::ZERO; rows * cols])
+    }
+
+    pub fn identity(n: usize) -> Matrix<T> {
+        let mut m = Matrix::zeros(n, n);
+        (0..n).for_each(|i| m[(i, i)] = T::ONE);
+        m
+    }
+
+    pub fn transpose(&self) -> Matrix<T> {
+        let cells = (0..self.cols)
+            .flat_map(|c| (0..self.rows).map(move |r| self[(r, c)]))
+            .collect();
+        Matrix::new(self.cols, self.rows, cells)
+    }
+
+    /// Places `other` to the right of this matrix. Panics if the row counts differ.
+    pub fn augment(&self, other: &Matrix<T>) -> Matrix<T> {
+        assert_eq!(self.rows, other.rows, "Cannot augment {} rows with {} rows", self.rows, other.rows);
+        let cells = (0..self.rows)
+            .flat_map(|r| self.row(r).iter().chain(other.row(r)).copied())
+            .collect();
+        Matrix::new(self.rows, self.cols + other.cols, cells)
+    }
+
+    /// Multiplies with a column vector. Panics if the lengths do not match.
+    pub fn mul_vector(&self, v: &[T]) -> Vec<T> {
+        assert_eq!(self.cols, v.len(), "Cannot multiply {} columns with a vector of {}", self.cols, v.len());
+        (0..self.rows)
+            .map(|r| self.row(r).iter().zip(v).fold(T::ZERO, |sum, (a, b)| sum + *a * *b))
+            .collect()
+    }
+
+    /// Gauss-Jordan elimination into reduced row echelon form, pivoting on the first non-zero entry.
+    pub fn echelon(&self) -> Echelon<T> {
+        self.echelon_until(self.cols)
+    }
+
+    /// Like `echelon`, but only picks pivots in the first `cols` columns.
+    /// Eliminating an augmented matrix this way keeps the right-hand side out of the pivots.
+    fn echelon_until(&self, cols: usize) -> Echelon<T> {
+        let mut m = self.clone();
+        let mut pivots = Vec::new();
+        let mut determinant = T::ONE;
+        for c in 0..cols {
+            let row = pivots.len();
+            let Some(r) = (row..m.rows).find(|r| !m[(*r, c)].is_zero()) else {
+                determinant = T::ZERO;
+                continue;
+            };
+            if r != row {
+                m.swap_rows(r, row);
+                determinant = -determinant;
+            }
+            let pivot = m[(row, c)];
+            determinant = determinant * pivot;
+            for x in c..m.cols {
+                m[(row, x)] = m[(row, x)] / pivot;
+            }
+            for other in (0..m.rows).filter(|o| *o != row) {
+                let factor = m[(other, c)];
+                if !factor.is_zero() {
+                    for x in c..m.cols {
+                        m[(other, x)] = m[(other, x)] - factor * m[(row, x)];
+                    }
+                }
+            }
+            pivots.push(c);
+        }
+        Echelon { matrix: m, pivots, determinant }
+    }
+
+    pub fn rank(&self) -> usize {
+        self.echelon().rank()
+    }
+
+    /// Panics if the matrix is not square.
+    pub fn determinant(&self) -> T {
+        assert!(self.is_square(), "Determinant of a {}x{} matrix", self.rows, self.cols);
+        let echelon = self.echelon();
+        if echelon.rank() < self.rows { T::ZERO } else { echelon.determinant }
+    }
+
+    /// The inverse, `None` if the matrix is singular. Panics if the matrix is not square.
+    pub fn inverse(&self) -> Option<Matrix<T>> {
+        assert!(self.is_square(), "Inverse of a {}x{} matrix", self.rows, self.cols);
+        let n = self.rows;
+        let echelon = self.augment(&Matrix::identity(n)).echelon_until(n);
+        (echelon.rank() == n).then(|| {
+            let cells = (0..n).flat_map(|r| echelon.matrix.row(r)[n..].to_vec()).collect();
+            Matrix::new(n, n, cells)
+        })
+    }
+
+    /// Solves `self * x = b`. Panics if `b` does not have one entry per row.
+    pub fn solve(&self, b: &[T]) -> Solution<T> {
+        assert_eq!(self.rows, b.len(), "Right-hand side of {} for {} rows", b.len(), self.rows);
+        let column = Matrix::new(b.len(), 1, b.to_vec());
+        let echelon = self.augment(&column).echelon_until(self.cols);
+        let m = &echelon.matrix;
+        // A zero row with a non-zero right-hand side reads 0 = c
+        if (echelon.rank()..m.rows).any(|r| !m[(r, self.cols)].is_zero()) {
+            return Solution::None;
+        }
+        let mut particular = vec![T::ZERO; self.cols];
+        for (r, c) in echelon.pivots.iter().enumerate() {
+            particular[*c] = m[(r, self.cols)];
+        }
+        if echelon.rank() == self.cols {
+            Solution::Unique(particular)
+        } else {
+            let free = (0..self.cols).filter(|c| !echelon.pivots.contains(c)).collect();
+            Solution::Infinite { particular, free }
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (r, c): (usize, usize)) -> &Self::Output {
+        assert!(c < self.cols, "Column {} out of {}", c, self.cols);
+        &self.cells[r * self.cols + c]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (r, c): (usize, usize)) -> &mut Self::Output {
+        assert!(c < self.cols, "Column {} out of {}", c, self.cols);
+        &mut self.cells[r * self.cols + c]
+    }
+}
+
+impl<T: Field> Mul for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    /// Panics if the inner dimensions do not match.
+    fn mul(self, other: &Matrix<T>) -> Matrix<T> {
+        assert_eq!(self.cols, other.rows, "Cannot multiply {}x{} by {}x{}", self.rows, self.cols, other.rows, other.cols);
+        let cells = (0..self.rows)
+            .flat_map(|r| (0..other.cols).map(move |c| (0..self.cols)
+                .fold(T::ZERO, |sum, i| sum + self[(r, i)] * other[(i, c)])))
+            .collect();
+        Matrix::new(self.rows, other.cols, cells)
+    }
+}
+
+impl<T: Display> Display for Matrix<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for r in 0..self.rows {
+            let row: Vec<String> = self.row(r).iter().map(|v| v.to_string()).collect();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type F7 = ModP<7>;
+
+    fn rationals(rows: &[&[i64]]) -> Matrix<Rational> {
+        Matrix::from_rows(rows.iter().map(|r| r.iter().map(|v| Rational::from(*v)).collect()).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_mod_p() {
+        assert_eq!(F7::new(-1).value(), 6);
+        assert_eq!(F7::new(3) * F7::new(5), F7::new(1));
+        assert_eq!(F7::new(3).inverse(), F7::new(5));
+        assert_eq!(F7::new(2) / F7::new(4), F7::new(4));
+        assert_eq!(-F7::new(0), F7::ZERO);
+        assert_eq!(F7::new(2) - F7::new(5), F7::new(4));
+    }
+
+    #[test]
+    fn test_multiply_and_transpose() {
+        let a = rationals(&[&[1, 2, 3], &[4, 5, 6]]);
+        let b = rationals(&[&[7, 8], &[9, 10], &[11, 12]]);
+        assert_eq!(&a * &b, rationals(&[&[58, 64], &[139, 154]]));
+        assert_eq!(a.transpose(), rationals(&[&[1, 4], &[2, 5], &[3, 6]]));
+        assert_eq!(&a * &Matrix::identity(3), a);
+        assert_eq!(a.mul_vector(&[1.into(), 0.into(), Rational::new(-1, 3)]), vec![0.into(), 2.into()]);
+        assert_eq!(a.to_string(), "1 2 3\n4 5 6\n");
+    }
+
+    #[test]
+    fn test_determinant_and_inverse() {
+        let a = rationals(&[&[2, 1], &[7, 4]]);
+        assert_eq!(a.determinant(), Rational::ONE);
+        assert_eq!(a.inverse(), Some(rationals(&[&[4, -1], &[-7, 2]])));
+
+        // Needs a row swap, which flips the sign
+        let b = rationals(&[&[0, 1, 2], &[1, 0, 3], &[4, -3, 8]]);
+        assert_eq!(b.determinant(), Rational::from(-2));
+        let inverse = b.inverse().unwrap();
+        assert_eq!(&b * &inverse, Matrix::identity(3));
+        assert_eq!(inverse[(0, 0)], Rational::new(-9, 2));
+
+        let singular = rationals(&[&[1, 2], &[2, 4]]);
+        assert_eq!(singular.determinant(), Rational::ZERO);
+        assert_eq!(singular.inverse(), None);
+        assert_eq!(singular.rank(), 1);
+    }
+
+    #[test]
+    fn test_solve() {
+        // x + y = 3, x - y = 1
+        let a = rationals(&[&[1, 1], &[1, -1]]);
+        assert_eq!(a.solve(&[3.into(), 1.into()]), Solution::Unique(vec![2.into(), 1.into()]));
+
+        // x + 2y = 1 twice has a line of solutions, with y free
+        let b = rationals(&[&[1, 2], &[2, 4]]);
+        assert_eq!(b.solve(&[1.into(), 2.into()]),
+                   Solution::Infinite { particular: vec![1.into(), 0.into()], free: vec![1] });
+        assert_eq!(b.solve(&[1.into(), 3.into()]), Solution::None);
+
+        // Fractional solution: 2x = 1, 3y = 2
+        let c = rationals(&[&[2, 0], &[0, 3]]);
+        assert_eq!(c.solve(&[1.into(), 2.into()]), Solution::Unique(vec![Rational::new(1, 2), Rational::new(2, 3)]));
+    }
+
+    #[test]
+    fn test_modular() {
+        let a: Matrix<F7> = rationals(&[&[1, 2], &[3, 4]]).map(|v| F7::new(v.numer() as i64));
+        // The determinant is -2, that is 5 mod 7
+        assert_eq!(a.determinant(), F7::new(5));
+        assert_eq!(&a * &a.inverse().unwrap(), Matrix::identity(2));
+        assert_eq!(a.solve(&[F7::new(5), F7::new(6)]), Solution::Unique(vec![F7::new(3), F7::new(1)]));
+        // Rank depends on the field: this is singular mod 7 but not over the rationals
+        let b = rationals(&[&[1, 2], &[3, 13]]);
+        assert_eq!(b.rank(), 2);
+        assert_eq!(b.map(|v| F7::new(v.numer() as i64)).rank(), 1);
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::rc::Rc;
+
+/// Counts how often a cache could answer a lookup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Share of lookups answered from the cache, 0 before the first lookup.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 { 0.0 } else { self.hits as f64 / total as f64 }
+    }
+}
+
+impl Display for CacheStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} hits, {} misses ({:.1}% hit rate)", self.hits, self.misses, self.hit_rate() * 100.0)
+    }
+}
+
+/// A keyed cache that keeps statistics about its lookups.
+#[derive(Debug, Clone)]
+pub struct Cache<K, V> {
+    values: HashMap<K, V>,
+    stats: CacheStats,
+}
+
+impl<K, V> Default for Cache<K, V> {
+    fn default() -> Self {
+        Cache { values: HashMap::new(), stats: CacheStats::default() }
+    }
+}
+
+impl<K, V> Cache<K, V>
+    where K: Eq + Hash, V: Clone {
+    pub fn new() -> Self {
+        Cache::default()
+    }
+
+    /// Looks up a value, counting a hit or a miss.
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        let value = self.values.get(key).cloned();
+        match value {
+            Some(_) => self.stats.hits += 1,
+            None => self.stats.misses += 1,
+        }
+        value
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.values.insert(key, value);
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Forgets all values and resets the statistics.
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.stats = CacheStats::default();
+    }
+}
+
+type Recursive<'a, K, V> = Rc<dyn Fn(&mut Memo<'a, K, V>, &K) -> V + 'a>;
+
+/// Memoizes a recursive function given as a closure.
+/// The closure receives the memo itself to make its recursive calls through the cache.
+///
+/// ```
+/// use aoc2023::aoc::tools::memo::Memo;
+///
+/// let mut fib = Memo::new(|memo, n: &u64| if *n < 2 { *n } else { memo.get(n - 1) + memo.get(n - 2) });
+/// assert_eq!(fib.get(90), 2880067194370816120);
+/// ```
+pub struct Memo<'a, K, V> {
+    f: Recursive<'a, K, V>,
+    cache: Cache<K, V>,
+}
+
+impl<'a, K, V> Memo<'a, K, V>
+    where K: Eq + Hash, V: Clone {
+    pub fn new(f: impl Fn(&mut Memo<'a, K, V>, &K) -> V + 'a) -> Self {
+        Memo { f: Rc::new(f), cache: Cache::new() }
+    }
+
+    pub fn get(&mut self, key: K) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            return value;
+        }
+        // The cache must not be borrowed while the function recurses
+        let f = Rc::clone(&self.f);
+        let value = f(self, &key);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+}
+
+/// Memoizes a free function, with a thread local cache of the given name for statistics.
+/// The arguments form the key, so they need to be `Clone + Eq + Hash`.
+///
+/// ```
+/// use aoc2023::memoize;
+///
+/// memoize! {
+///     PATHS;
+///     fn paths(width: u64, height: u64) -> u64 {
+///         if width == 0 || height == 0 { 1 } else { paths(width - 1, height) + paths(width, height - 1) }
+///     }
+/// }
+///
+/// assert_eq!(paths(16, 16), 601080390);
+/// assert!(PATHS.with(|cache| cache.borrow().stats().hits) > 0);
+/// ```
+#[macro_export]
+macro_rules! memoize {
+    ($cache:ident; $vis:vis fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty $body:block) => {
+        thread_local! {
+            $vis static $cache: std::cell::RefCell<$crate::aoc::tools::memo::Cache<($($ty,)*), $ret>> =
+                std::cell::RefCell::new($crate::aoc::tools::memo::Cache::new());
+        }
+
+        $vis fn $name($($arg: $ty),*) -> $ret {
+            fn uncached($($arg: $ty),*) -> $ret $body
+
+            let key = ($($arg.clone(),)*);
+            if let Some(value) = $cache.with(|cache| cache.borrow_mut().get(&key)) {
+                return value;
+            }
+            let value = uncached($($arg),*);
+            $cache.with(|cache| cache.borrow_mut().insert(key, value.clone()));
+            value
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    memoize! {
+        COLLATZ;
+        fn collatz(n: u64) -> u64 {
+            match n {
+                1 => 0,
+                n if n % 2 == 0 => 1 + collatz(n / 2),
+                n => 1 + collatz(3 * n + 1),
+            }
+        }
+    }
+
+    #[test]
+    fn test_memo() {
+        let mut fib = Memo::new(|memo, n: &u64| if *n < 2 { *n } else { memo.get(n - 1) + memo.get(n - 2) });
+        assert_eq!(fib.get(50), 12586269025);
+        // Every value is computed once, the second recursive call always hits
+        assert_eq!(fib.stats(), CacheStats { hits: 48, misses: 51 });
+        assert_eq!(fib.get(50), 12586269025);
+        assert_eq!(fib.stats().hits, 49);
+    }
+
+    #[test]
+    fn test_memoize_macro() {
+        assert_eq!(collatz(27), 111);
+        let misses = COLLATZ.with(|c| c.borrow().stats().misses);
+        assert_eq!(collatz(27), 111);
+        assert_eq!(collatz(54), 112);
+        COLLATZ.with(|c| {
+            let stats = c.borrow().stats();
+            assert_eq!(stats.misses, misses + 1);
+            assert_eq!(stats.hits, 2);
+        });
+    }
+
+    #[test]
+    fn test_stats_display() {
+        let stats = CacheStats { hits: 3, misses: 1 };
+        assert_eq!(stats.to_string(), "3 hits, 1 misses (75.0% hit rate)");
+    }
+}
@@ -0,0 +1,220 @@
+use std::any::type_name;
+use std::fmt::Debug;
+use std::str::FromStr;
+
+pub use grid::{Grid, GridError};
+pub use point::{Direction, Point, Vector};
+
+pub mod bigint;
+pub mod bitset;
+pub mod checked;
+pub mod components;
+pub mod cycle;
+pub mod geometry;
+pub mod grid;
+pub mod interval;
+pub mod math;
+pub mod matrix;
+pub mod memo;
+pub mod parse;
+pub mod point;
+pub mod prop;
+pub mod random;
+pub mod rational;
+pub mod search;
+
+pub fn string_to_digit(s: &str) -> Option<i32> {
+    match s {
+        "zero" => Some(0),
+        "one" => Some(1),
+        "two" => Some(2),
+        "three" => Some(3),
+        "four" => Some(4),
+        "five" => Some(5),
+        "six" => Some(6),
+        "seven" => Some(7),
+        "eight" => Some(8),
+        "nine" => Some(9),
+        "0" => Some(0),
+        "1" => Some(1),
+        "2" => Some(2),
+        "3" => Some(3),
+        "4" => Some(4),
+        "5" => Some(5),
+        "6" => Some(6),
+        "7" => Some(7),
+        "8" => Some(8),
+        "9" => Some(9),
+        _ => None,
+    }
+}
+
+/// Parses an input string by splitting at a new line, stripping whitespace characters and returning it as a list
+pub fn read_lines(input: &str) -> Vec<String> {
+    input
+        .trim()
+        .split("\n")
+        .map(|s| s.trim().to_string())
+        .collect()
+}
+
+
+/// Parses and input string by splitting at a double new line, then splits each line at a single new line
+/// Optionally a block can contain a header, that is a string that if matched in the first line, it will be ignored
+pub fn read_blocks(input: &str, header: Option<&str>) -> Vec<Vec<String>> {
+    let mut blocks = Vec::new();
+    let mut block = Vec::new();
+    for raw_block in input.trim().split("\n\n") {
+        let mut lines = raw_block.trim()
+            .split("\n")
+            .map(|s| s.trim().to_string());
+
+        let mut first = true;
+        for line in lines.by_ref() {
+            if let Some(header) = header {
+                if first {
+                    first = false;
+                    if line.contains(header) {
+                        continue;
+                    }
+                }
+            }
+            block.push(line.to_string());
+        }
+
+        blocks.push(block.clone());
+        block.clear();
+    }
+    blocks
+}
+
+/// Parses an input string by splitting each line at whitespaces and returns the columns.
+/// Fails with the first row that has a different amount of columns than the first one.
+pub fn read_columns(input: &str) -> Result<Vec<Vec<String>>, GridError> {
+    let rows = read_lines(input).iter()
+        .map(|r| r.split_whitespace()
+            .map(|s| s.to_string()).collect())
+        .collect();
+
+    let grid = Grid::from_rows(rows)?;
+    Ok(grid.columns()
+        .map(|c| c.cloned().collect())
+        .collect())
+}
+
+/// Extracts every integer from arbitrary text, ignoring anything in between.
+/// A `-` directly in front of digits makes the number negative, unless it follows a digit, so `3-4` yields 3 and 4.
+/// Panics if a number does not fit into `T`, which includes negative numbers for unsigned types.
+pub fn ints<T>(input: &str) -> Vec<T>
+    where T: FromStr, T::Err: Debug {
+    let bytes = input.as_bytes();
+    let mut numbers = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let sign = bytes[i] == b'-'
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+            && (i == 0 || !bytes[i - 1].is_ascii_digit());
+        if !sign && !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        let number = &input[start..i];
+        numbers.push(number.parse()
+            .unwrap_or_else(|e| panic!("Cannot parse '{}' as {}: {:?}", number, type_name::<T>(), e)));
+    }
+    numbers
+}
+
+/// The integers of each non-empty line, see `ints`.
+pub fn ints_per_line<T>(input: &str) -> Vec<Vec<T>>
+    where T: FromStr, T::Err: Debug {
+    read_lines(input).iter()
+        .filter(|l| !l.is_empty())
+        .map(|l| ints(l))
+        .collect()
+}
+
+/// The integers of each block of lines separated by an empty line, see `ints`.
+pub fn ints_per_block<T>(input: &str) -> Vec<Vec<T>>
+    where T: FromStr, T::Err: Debug {
+    read_blocks(input, None).iter()
+        .map(|b| ints(&b.join("\n")))
+        .collect()
+}
+
+/// Given an input string and a predicate, returns the first character matching the predicate
+pub fn find_char(input: &str, predicate: fn(char) -> bool) -> Option<char> {
+    input
+        .chars()
+        .find(|c| predicate(*c))
+}
+
+/// Given an input string and a predicate, returns the first character matching the predicate
+pub fn rfind_char(input: &str, predicate: fn(char) -> bool) -> Option<char> {
+    input
+        .chars()
+        .rev()
+        .find(|c| predicate(*c))
+}
+
+
+/// Given an input string and a list of substrings returns the first occurrence of any of the substrings.
+/// The value with the lowest start index within the input is returned.
+/// Example: find_substrings("1onetwo", vec!["two", "one", "1"]) returns "1"
+pub fn find_substrings(input: &str, substrings: &[&str]) -> Option<String> {
+    substrings
+        .iter()
+        .filter_map(|s| input.find(s).map(|i| (i, s)))
+        .min_by_key(|(i, _)| *i)
+        .map(|(_, s)| s.to_string())
+}
+
+/// Given an input string and a list of substrings returns the first occurrence of any of the substrings.
+/// The value with the highest start index within the input is returned.
+/// Example: rfind_substrings("1onetwo", vec!["1", "one", "two"]) returns "two"
+pub fn rfind_substrings(input: &str, substrings: &[&str]) -> Option<String> {
+    substrings
+        .iter()
+        .filter_map(|s| input.rfind(s).map(|i| (i, s)))
+        .max_by_key(|(i, _)| *i)
+        .map(|(_, s)| s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ints() {
+        assert_eq!(ints::<i64>("x=-5, y=12..-3; z=+7"), vec![-5, 12, -3, 7]);
+        assert_eq!(ints::<i64>("seed-to-soil 3-4 --1"), vec![3, 4, -1]);
+        assert_eq!(ints::<u8>("Card  12: 4 | 255"), vec![12, 4, 255]);
+        assert_eq!(ints::<i32>("no numbers"), Vec::<i32>::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot parse '-1' as u32")]
+    fn test_ints_unsigned() {
+        ints::<u32>("1 -1");
+    }
+
+    #[test]
+    fn test_ints_per_line_and_block() {
+        let input = "1 2\n-3\n\n4: 5\n6";
+        assert_eq!(ints_per_line::<i32>(input), vec![vec![1, 2], vec![-3], vec![4, 5], vec![6]]);
+        assert_eq!(ints_per_block::<i32>(input), vec![vec![1, 2, -3], vec![4, 5, 6]]);
+    }
+
+    #[test]
+    fn test_read_columns() {
+        assert_eq!(read_columns("a 1\nb 2"), Ok(vec![vec!["a".to_string(), "b".to_string()], vec!["1".to_string(), "2".to_string()]]));
+        assert_eq!(read_columns("a 1\nb"), Err(GridError::Ragged { row: 1, expected: 2, found: 1 }));
+        assert_eq!(read_columns(""), Ok(vec![]));
+    }
+}
@@ -0,0 +1,382 @@
+use std::any::type_name;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::aoc::tools::Grid;
+
+/// A parse failure with the position of the offending text, both starting at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A piece of the input that remembers where it starts, so errors can point at it.
+/// Splitting and matching a field yields fields again, keeping track of their positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field<'a> {
+    pub text: &'a str,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl<'a> Field<'a> {
+    /// The whole input, starting at line 1, column 1.
+    pub fn new(text: &'a str) -> Field<'a> {
+        Field { text, line: 1, column: 1 }
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError { line: self.line, column: self.column, message: message.into() }
+    }
+
+    /// A sub field of the given byte range. Takes time linear in the start of the range, so use a `Cursor`
+    /// for many slices of the same field.
+    pub fn slice(&self, range: Range<usize>) -> Field<'a> {
+        Cursor::new(*self).slice(range)
+    }
+
+    pub fn trim(&self) -> Field<'a> {
+        let start = self.text.len() - self.text.trim_start().len();
+        let end = self.text.trim_end().len();
+        self.slice(start..end.max(start))
+    }
+
+    /// Parses the trimmed text, the error names the expected type.
+    pub fn parse<T: FromStr>(&self) -> Result<T, ParseError> {
+        let field = self.trim();
+        field.text.parse()
+            .map_err(|_| field.error(format!("Expected {}, found '{}'", type_name::<T>(), field.text)))
+    }
+
+    /// Splits at every separator, the parts are trimmed.
+    pub fn split(&self, separator: &str) -> Vec<Field<'a>> {
+        let mut cursor = Cursor::new(*self);
+        let mut parts = Vec::new();
+        let mut start = 0;
+        for (i, _) in self.text.match_indices(separator) {
+            parts.push(cursor.slice(start..i).trim());
+            start = i + separator.len();
+        }
+        parts.push(cursor.slice(start..self.text.len()).trim());
+        parts
+    }
+
+    /// The whitespace separated words.
+    pub fn words(&self) -> Vec<Field<'a>> {
+        let mut cursor = Cursor::new(*self);
+        self.text.split_whitespace()
+            // split_whitespace yields sub slices, so their offset is the pointer difference
+            .map(|w| {
+                let start = w.as_ptr() as usize - self.text.as_ptr() as usize;
+                cursor.slice(start..start + w.len())
+            })
+            .collect()
+    }
+
+    /// Parses every whitespace separated word.
+    pub fn list<T: FromStr>(&self) -> Result<Vec<T>, ParseError> {
+        self.words().iter().map(|w| w.parse()).collect()
+    }
+
+    /// The non-empty lines, trimmed.
+    pub fn lines(&self) -> Vec<Field<'a>> {
+        let mut cursor = Cursor::new(*self);
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for line in self.text.split('\n') {
+            let field = cursor.slice(start..start + line.len()).trim();
+            if !field.text.is_empty() {
+                lines.push(field);
+            }
+            start += line.len() + 1;
+        }
+        lines
+    }
+
+    /// Groups of lines separated by blank lines, trimmed.
+    pub fn blocks(&self) -> Vec<Field<'a>> {
+        let mut cursor = Cursor::new(*self);
+        let mut blocks = Vec::new();
+        let mut block: Option<Range<usize>> = None;
+        let mut start = 0;
+        for line in self.text.split('\n') {
+            if line.trim().is_empty() {
+                blocks.extend(block.take().map(|b| cursor.slice(b).trim()));
+            } else {
+                let range = block.get_or_insert(start..start);
+                range.end = start + line.len();
+            }
+            start += line.len() + 1;
+        }
+        blocks.extend(block.map(|b| cursor.slice(b).trim()));
+        blocks
+    }
+
+    pub fn matches(&self, pattern: &Pattern) -> Result<Captures<'a>, ParseError> {
+        pattern.matches(*self)
+    }
+
+    /// A grid with one row per line, like `Grid::parse` but pointing at the offending character or row.
+    /// The mapping returns `None` for invalid characters, blank lines are skipped.
+    pub fn grid<T>(&self, mut f: impl FnMut(char) -> Option<T>) -> Result<Grid<T>, ParseError> {
+        let lines = self.lines();
+        let mut rows: Vec<Vec<T>> = Vec::with_capacity(lines.len());
+        for line in lines {
+            let row = line.text.char_indices()
+                .map(|(i, c)| f(c).ok_or_else(|| line.slice(i..i + c.len_utf8()).error(format!("Invalid character '{}'", c))))
+                .collect::<Result<Vec<T>, ParseError>>()?;
+            if let Some(first) = rows.first().filter(|first| first.len() != row.len()) {
+                return Err(line.error(format!("Expected {} cells, found {}", first.len(), row.len())));
+            }
+            rows.push(row);
+        }
+        Ok(Grid::from_rows(rows).expect("Rows have the same length"))
+    }
+}
+
+/// A position within a field that only moves forward, so slicing a field piece by piece scans its text once.
+pub struct Cursor<'a> {
+    field: Field<'a>,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(field: Field<'a>) -> Cursor<'a> {
+        Cursor { field, offset: 0, line: field.line, column: field.column }
+    }
+
+    /// A sub field of the given byte range, like `Field::slice`. Only the text since the previous slice is
+    /// scanned, so the ranges have to start in increasing order. Panics if a range starts before the previous one.
+    pub fn slice(&mut self, range: Range<usize>) -> Field<'a> {
+        let skipped = &self.field.text[self.offset..range.start];
+        match skipped.rfind('\n') {
+            Some(i) => {
+                self.line += skipped.matches('\n').count();
+                self.column = skipped[i + 1..].chars().count() + 1;
+            }
+            None => self.column += skipped.chars().count(),
+        }
+        self.offset = range.start;
+        Field { text: &self.field.text[range], line: self.line, column: self.column }
+    }
+}
+
+/// Parses every non-empty line of the input, stopping at the first error.
+pub fn parse_lines<T>(input: &str, f: impl FnMut(Field) -> Result<T, ParseError>) -> Result<Vec<T>, ParseError> {
+    Field::new(input).lines().into_iter().map(f).collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Capture(String),
+}
+
+/// Literal text with named placeholders, e.g. `"Card {id}: {winning} | {numbers}"`.
+/// A placeholder captures everything up to the next literal, or the rest of the text if it comes last.
+/// Captured fields are trimmed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    parts: Vec<Part>,
+}
+
+impl Pattern {
+    /// Panics on unbalanced braces or two placeholders without a literal in between, as the
+    /// pattern is part of the code and not of the input.
+    pub fn new(pattern: &str) -> Pattern {
+        let mut parts = Vec::new();
+        let mut rest = pattern;
+        while !rest.is_empty() {
+            match rest.find('{') {
+                Some(0) => {
+                    let end = rest.find('}').unwrap_or_else(|| panic!("Unclosed placeholder in '{}'", pattern));
+                    if matches!(parts.last(), Some(Part::Capture(_))) {
+                        panic!("Placeholders need a literal in between in '{}'", pattern);
+                    }
+                    parts.push(Part::Capture(rest[1..end].to_string()));
+                    rest = &rest[end + 1..];
+                }
+                found => {
+                    let end = found.unwrap_or(rest.len());
+                    assert!(!rest[..end].contains('}'), "Unopened placeholder in '{}'", pattern);
+                    parts.push(Part::Literal(rest[..end].to_string()));
+                    rest = &rest[end..];
+                }
+            }
+        }
+        Pattern { parts }
+    }
+
+    pub fn matches<'a>(&self, field: Field<'a>) -> Result<Captures<'a>, ParseError> {
+        let text = field.text;
+        let mut cursor = Cursor::new(field);
+        let mut captures = Vec::new();
+        let mut pos = 0;
+        for (i, part) in self.parts.iter().enumerate() {
+            match part {
+                Part::Literal(literal) => {
+                    if !text[pos..].starts_with(literal.as_str()) {
+                        return Err(cursor.slice(pos..text.len())
+                            .error(format!("Expected '{}', found '{}'", literal, &text[pos..])));
+                    }
+                    pos += literal.len();
+                }
+                Part::Capture(name) => {
+                    let end = match self.parts.get(i + 1) {
+                        Some(Part::Literal(next)) => text[pos..].find(next.as_str())
+                            .map(|j| pos + j)
+                            .ok_or_else(|| cursor.slice(text.len()..text.len())
+                                .error(format!("Expected '{}', found end of line", next)))?,
+                        _ => text.len(),
+                    };
+                    captures.push((name.clone(), cursor.slice(pos..end).trim()));
+                    pos = end;
+                }
+            }
+        }
+        if pos < text.len() {
+            return Err(cursor.slice(pos..text.len()).error(format!("Unexpected '{}'", &text[pos..])));
+        }
+        Ok(Captures { fields: captures })
+    }
+}
+
+/// The fields captured by the placeholders of a pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Captures<'a> {
+    fields: Vec<(String, Field<'a>)>,
+}
+
+impl<'a> Captures<'a> {
+    /// Panics if the pattern has no placeholder of that name.
+    pub fn get(&self, name: &str) -> Field<'a> {
+        self.fields.iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, f)| *f)
+            .unwrap_or_else(|| panic!("Pattern has no placeholder '{}'", name))
+    }
+
+    pub fn parse<T: FromStr>(&self, name: &str) -> Result<T, ParseError> {
+        self.get(name).parse()
+    }
+
+    pub fn list<T: FromStr>(&self, name: &str) -> Result<Vec<T>, ParseError> {
+        self.get(name).list()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = r#"
+        Card 1: 41 48 | 83  86 6
+        Card 2: 13 x2 | 61 30
+    "#;
+
+    #[test]
+    fn test_pattern() {
+        let pattern = Pattern::new("Card {id}: {winning} | {numbers}");
+        let lines = Field::new(INPUT).lines();
+        assert_eq!(lines.len(), 2);
+
+        let card = pattern.matches(lines[0]).unwrap();
+        assert_eq!(card.parse::<u32>("id"), Ok(1));
+        assert_eq!(card.list::<u32>("winning"), Ok(vec![41, 48]));
+        assert_eq!(card.list::<u32>("numbers"), Ok(vec![83, 86, 6]));
+        assert_eq!(card.get("numbers").column, 25);
+
+        let card = lines[1].matches(&pattern).unwrap();
+        let error = card.list::<u32>("winning").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 20));
+        assert_eq!(error.to_string(), "line 3, column 20: Expected u32, found 'x2'");
+    }
+
+    #[test]
+    fn test_pattern_errors() {
+        let pattern = Pattern::new("{id} = ({left}, {right})");
+        assert!(pattern.matches(Field::new("AAA = (BBB, CCC)")).is_ok());
+
+        let error = Pattern::new("Card {id}: {numbers}").matches(Field::new("Cart 1: 2")).unwrap_err();
+        assert_eq!(error.column, 1);
+        assert_eq!(error.message, "Expected 'Card ', found 'Cart 1: 2'");
+
+        let error = pattern.matches(Field::new("AAA = (BBB CCC)")).unwrap_err();
+        assert_eq!(error.column, 16);
+        assert_eq!(error.message, "Expected ', ', found end of line");
+
+        let error = pattern.matches(Field::new("AAA = (BBB, CCC))")).unwrap_err();
+        assert_eq!(error.column, 17);
+        assert_eq!(error.message, "Unexpected ')'");
+    }
+
+    #[test]
+    #[should_panic(expected = "literal in between")]
+    fn test_ambiguous_pattern() {
+        Pattern::new("{a}{b}");
+    }
+
+    #[test]
+    fn test_split_and_blocks() {
+        let input = "RL\n\nAAA = (BBB, CCC)\n  BBB = (DDD, EEE)\n";
+        let blocks = Field::new(input).blocks();
+        assert_eq!(blocks.iter().map(|b| b.text).collect::<Vec<_>>(), vec!["RL", "AAA = (BBB, CCC)\n  BBB = (DDD, EEE)"]);
+        let lines = blocks[1].lines();
+        assert_eq!((lines[1].text, lines[1].line, lines[1].column), ("BBB = (DDD, EEE)", 4, 3));
+
+        let parts = Field::new("3 blue, 4 red; 1 red").split(";");
+        assert_eq!(parts[1].text, "1 red");
+        assert_eq!(parts[1].column, 16);
+        assert_eq!(parts[0].split(",")[1].words()[1].column, 11);
+    }
+
+    #[test]
+    fn test_large_input() {
+        // Quadratic position tracking takes minutes here
+        let input = "Card 1: 41 48 | 83 86\n".repeat(200_000);
+        let lines = Field::new(&input).lines();
+        let words = lines[199_999].words();
+        assert_eq!((words[6].text, words[6].line, words[6].column), ("86", 200_000, 20));
+        let card = Pattern::new("Card {id}: {winning} | {numbers}").matches(lines[199_999]).unwrap();
+        assert_eq!(card.get("numbers").words()[1].column, 20);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cursor_backwards() {
+        let mut cursor = Cursor::new(Field::new("ab\ncd"));
+        assert_eq!(cursor.slice(3..4).line, 2);
+        cursor.slice(0..1);
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        assert_eq!(parse_lines("1\n\n2\n3", |l| l.parse::<i32>()), Ok(vec![1, 2, 3]));
+        assert_eq!(parse_lines("1\n\nz", |l| l.parse::<i32>()).unwrap_err().line, 3);
+    }
+
+    #[test]
+    fn test_grid() {
+        let grid = Field::new("\n  .#\n  #.\n").grid(|c| (c != 'x').then_some(c == '#')).unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 2));
+        assert!(grid[crate::aoc::tools::Point::new(1, 0)]);
+
+        let error = Field::new(".#\n.x").grid(|c| (c != 'x').then_some(c)).unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 2: Invalid character 'x'");
+        let error = Field::new("..\n\n...").grid(Some).unwrap_err();
+        assert_eq!(error.to_string(), "line 3, column 1: Expected 2 cells, found 3");
+    }
+}
@@ -0,0 +1,257 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A position on an integer grid. `y` grows downwards, like the rows of a puzzle input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+/// The difference between two points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Vector {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub const fn new(x: i64, y: i64) -> Point {
+        Point { x, y }
+    }
+
+    /// Taxicab distance, the number of orthogonal steps between two points.
+    pub fn manhattan(&self, other: Point) -> u64 {
+        (*self - other).manhattan()
+    }
+
+    /// Chessboard distance, the number of king moves between two points.
+    pub fn chebyshev(&self, other: Point) -> u64 {
+        (*self - other).chebyshev()
+    }
+
+    /// Converts to unsigned `(x, y)` indices, `None` if a coordinate is negative.
+    pub fn to_index(&self) -> Option<(usize, usize)> {
+        Some((usize::try_from(self.x).ok()?, usize::try_from(self.y).ok()?))
+    }
+}
+
+impl Vector {
+    pub const fn new(x: i64, y: i64) -> Vector {
+        Vector { x, y }
+    }
+
+    pub fn manhattan(&self) -> u64 {
+        self.x.unsigned_abs() + self.y.unsigned_abs()
+    }
+
+    pub fn chebyshev(&self) -> u64 {
+        self.x.unsigned_abs().max(self.y.unsigned_abs())
+    }
+}
+
+impl From<(usize, usize)> for Point {
+    fn from((x, y): (usize, usize)) -> Self {
+        Point::new(x as i64, y as i64)
+    }
+}
+
+impl From<(i64, i64)> for Point {
+    fn from((x, y): (i64, i64)) -> Self {
+        Point::new(x, y)
+    }
+}
+
+impl From<Point> for (i64, i64) {
+    fn from(p: Point) -> Self {
+        (p.x, p.y)
+    }
+}
+
+impl Display for Point {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl Add<Vector> for Point {
+    type Output = Point;
+
+    fn add(self, v: Vector) -> Point {
+        Point::new(self.x + v.x, self.y + v.y)
+    }
+}
+
+impl AddAssign<Vector> for Point {
+    fn add_assign(&mut self, v: Vector) {
+        *self = *self + v;
+    }
+}
+
+impl Sub<Vector> for Point {
+    type Output = Point;
+
+    fn sub(self, v: Vector) -> Point {
+        Point::new(self.x - v.x, self.y - v.y)
+    }
+}
+
+impl SubAssign<Vector> for Point {
+    fn sub_assign(&mut self, v: Vector) {
+        *self = *self - v;
+    }
+}
+
+impl Sub<Point> for Point {
+    type Output = Vector;
+
+    fn sub(self, other: Point) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Vector) -> Vector {
+        Vector::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, other: Vector) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<i64> for Vector {
+    type Output = Vector;
+
+    fn mul(self, factor: i64) -> Vector {
+        Vector::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector::new(-self.x, -self.y)
+    }
+}
+
+/// A compass direction, north pointing up (towards negative `y`).
+/// The discriminant counts eighths of a turn clockwise from north.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    N = 0,
+    NE = 1,
+    E = 2,
+    SE = 3,
+    S = 4,
+    SW = 5,
+    W = 6,
+    NW = 7,
+}
+
+impl Direction {
+    /// The four orthogonal directions, clockwise starting north.
+    pub const CARDINAL: [Direction; 4] = [Direction::N, Direction::E, Direction::S, Direction::W];
+
+    /// All eight directions, clockwise starting north.
+    pub const ALL: [Direction; 8] = [
+        Direction::N, Direction::NE, Direction::E, Direction::SE,
+        Direction::S, Direction::SW, Direction::W, Direction::NW,
+    ];
+
+    /// The unit step in this direction, diagonals move one step on both axes.
+    pub fn vector(&self) -> Vector {
+        match self {
+            Direction::N => Vector::new(0, -1),
+            Direction::NE => Vector::new(1, -1),
+            Direction::E => Vector::new(1, 0),
+            Direction::SE => Vector::new(1, 1),
+            Direction::S => Vector::new(0, 1),
+            Direction::SW => Vector::new(-1, 1),
+            Direction::W => Vector::new(-1, 0),
+            Direction::NW => Vector::new(-1, -1),
+        }
+    }
+
+    /// Rotates by the given number of eighths of a turn, clockwise for positive values.
+    pub fn rotate(&self, eighths: i32) -> Direction {
+        Direction::ALL[(*self as i32 + eighths).rem_euclid(8) as usize]
+    }
+
+    /// Rotates a quarter turn counter-clockwise.
+    pub fn turn_left(&self) -> Direction {
+        self.rotate(-2)
+    }
+
+    /// Rotates a quarter turn clockwise.
+    pub fn turn_right(&self) -> Direction {
+        self.rotate(2)
+    }
+
+    pub fn reverse(&self) -> Direction {
+        self.rotate(4)
+    }
+
+    pub fn is_cardinal(&self) -> bool {
+        (*self as u8).is_multiple_of(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let mut p = Point::new(2, 3);
+        let v = Vector::new(-1, 4);
+        assert_eq!(p + v, Point::new(1, 7));
+        assert_eq!(p - v, Point::new(3, -1));
+        assert_eq!(Point::new(1, 7) - p, v);
+        assert_eq!(v * 3, Vector::new(-3, 12));
+        assert_eq!(-v, Vector::new(1, -4));
+        p += v;
+        p -= v + v;
+        assert_eq!(p, Point::new(3, -1));
+    }
+
+    #[test]
+    fn test_distances() {
+        let a = Point::new(1, 6);
+        let b = Point::new(5, 11);
+        assert_eq!(a.manhattan(b), 9);
+        assert_eq!(b.manhattan(a), 9);
+        assert_eq!(a.chebyshev(b), 5);
+    }
+
+    #[test]
+    fn test_to_index() {
+        assert_eq!(Point::new(3, 0).to_index(), Some((3, 0)));
+        assert_eq!(Point::new(3, -1).to_index(), None);
+        assert_eq!(Point::from((4usize, 5usize)), Point::new(4, 5));
+    }
+
+    #[test]
+    fn test_turns() {
+        assert_eq!(Direction::N.turn_right(), Direction::E);
+        assert_eq!(Direction::N.turn_left(), Direction::W);
+        assert_eq!(Direction::NE.turn_left(), Direction::NW);
+        assert_eq!(Direction::SW.reverse(), Direction::NE);
+        assert_eq!(Direction::W.rotate(3), Direction::NE);
+        assert!(Direction::S.is_cardinal());
+        assert!(!Direction::SE.is_cardinal());
+        for d in Direction::ALL {
+            assert_eq!(d.vector() + d.reverse().vector(), Vector::default());
+            assert_eq!(d.turn_left().turn_right(), d);
+        }
+    }
+}
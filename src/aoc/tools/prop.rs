@@ -0,0 +1,326 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::RangeInclusive;
+
+use crate::aoc::tools::random::Rng;
+
+enum Source {
+    Random(Rng),
+    /// Replays recorded choices, choices past the end are zero
+    Replay(Vec<u64>, usize),
+}
+
+/// Draws the random choices of a generator.
+/// Every choice is recorded, so a failing case can be shrunk by replaying smaller choices through the same generator.
+/// Smaller choices mean smaller values: ranges shrink towards their start, booleans towards `false`.
+pub struct Gen {
+    source: Source,
+    choices: Vec<u64>,
+    size: usize,
+    density: Option<u64>,
+}
+
+impl Gen {
+    pub fn new(seed: u64, size: usize) -> Gen {
+        Gen { source: Source::Random(Rng::new(seed)), choices: Vec::new(), size, density: None }
+    }
+
+    fn replay(choices: Vec<u64>, size: usize) -> Gen {
+        Gen { source: Source::Replay(choices, 0), choices: Vec::new(), size, density: None }
+    }
+
+    /// Fixes the share of filled cells of generated grids, in percent.
+    pub fn with_density(self, percent: u64) -> Gen {
+        Gen { density: Some(percent.min(100)), ..self }
+    }
+
+    /// How large generated values should be, grows over the cases of a check.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The share of filled cells set by `with_density`, generators choose one themselves otherwise.
+    pub fn density(&self) -> Option<u64> {
+        self.density
+    }
+
+    /// Lengths that grow with the size, from half the size up to the size.
+    /// Used for the main dimensions of an input, like the number of lines or the width of a grid.
+    pub fn scaled(&self) -> RangeInclusive<usize> {
+        self.size.div_ceil(2).max(1)..=self.size.max(1)
+    }
+
+    /// The choices drawn so far.
+    pub fn choices(&self) -> &[u64] {
+        &self.choices
+    }
+
+    /// A value in `0..bound`, any value without a bound.
+    fn draw(&mut self, bound: Option<u64>) -> u64 {
+        let value = match &mut self.source {
+            Source::Random(rng) => match bound {
+                Some(bound) => rng.below(bound),
+                None => rng.next_u64(),
+            },
+            Source::Replay(tape, position) => {
+                let value = tape.get(*position).copied().unwrap_or(0);
+                *position += 1;
+                bound.map_or(value, |bound| value.min(bound - 1))
+            }
+        };
+        self.choices.push(value);
+        value
+    }
+
+    /// A value in `0..bound`. Panics if the bound is zero.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "Cannot draw below 0");
+        self.draw(Some(bound))
+    }
+
+    /// An offset into a range of `span` values, which may be all `2^64` values.
+    fn offset(&mut self, span: u128) -> u128 {
+        self.draw(u64::try_from(span).ok()) as u128
+    }
+
+    /// A value in the inclusive range. Panics if the range is empty.
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (start, end) = range.into_inner();
+        assert!(start <= end, "Cannot draw from the empty range {}..={}", start, end);
+        let span = (end as i128 - start as i128 + 1) as u128;
+        (start as i128 + self.offset(span) as i128) as i64
+    }
+
+    /// A value in the inclusive range. Panics if the range is empty.
+    pub fn usize(&mut self, range: RangeInclusive<usize>) -> usize {
+        let (start, end) = range.into_inner();
+        assert!(start <= end, "Cannot draw from the empty range {}..={}", start, end);
+        let span = (end - start) as u128 + 1;
+        (start as u128 + self.offset(span)) as usize
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.below(2) == 1
+    }
+
+    /// True with the given chance in percent.
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) + percent >= 100
+    }
+
+    /// `None` for an empty slice, without drawing a choice.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        items.get(self.below(items.len() as u64) as usize)
+    }
+
+    pub fn vec<T>(&mut self, len: RangeInclusive<usize>, mut f: impl FnMut(&mut Gen) -> T) -> Vec<T> {
+        let len = self.usize(len);
+        (0..len).map(|_| f(self)).collect()
+    }
+}
+
+/// How many cases a check runs and how hard it tries to shrink a failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub cases: usize,
+    pub seed: u64,
+    /// The size of the last case, the first case has size 1
+    pub max_size: usize,
+    /// Upper bound on the replays while shrinking
+    pub max_shrinks: usize,
+}
+
+impl Default for Config {
+    /// A fixed seed keeps test runs reproducible, `AOC_PROP_SEED` overrides it to explore other cases.
+    fn default() -> Self {
+        let seed = std::env::var("AOC_PROP_SEED").ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0x5eed);
+        Config { cases: 100, seed, max_size: 20, max_shrinks: 2000 }
+    }
+}
+
+impl Config {
+    pub fn cases(self, cases: usize) -> Config {
+        Config { cases, ..self }
+    }
+
+    pub fn max_size(self, max_size: usize) -> Config {
+        Config { max_size, ..self }
+    }
+}
+
+/// A case for which a property does not hold, shrunk as far as possible.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure<T> {
+    pub value: T,
+    pub message: String,
+    /// The seed of the check and the case that failed first
+    pub seed: u64,
+    pub case: usize,
+    /// Number of successful shrinking steps
+    pub shrinks: usize,
+}
+
+impl<T: Debug> Display for Failure<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Case {} of seed {} failed, shrunk {} times: {}\n{:#?}",
+               self.case, self.seed, self.shrinks, self.message, self.value)
+    }
+}
+
+/// Compares the choices that produced two values, shorter and then smaller is simpler.
+fn simpler(a: &[u64], b: &[u64]) -> bool {
+    (a.len(), a) < (b.len(), b)
+}
+
+/// Smaller variants of a list of choices, the most promising first.
+fn candidates(choices: &[u64]) -> Vec<Vec<u64>> {
+    let mut candidates = Vec::new();
+    let mut len = choices.len() / 2;
+    while len > 0 {
+        candidates.push(choices[..choices.len() - len].to_vec());
+        len /= 2;
+    }
+    for chunk in [8, 4, 2, 1] {
+        for start in (0..choices.len().saturating_sub(chunk - 1)).rev() {
+            let mut shorter = choices.to_vec();
+            shorter.drain(start..start + chunk);
+            candidates.push(shorter);
+        }
+    }
+    for i in 0..choices.len() {
+        for smaller in [0, choices[i] / 2, choices[i].saturating_sub(1)] {
+            if smaller < choices[i] {
+                let mut reduced = choices.to_vec();
+                reduced[i] = smaller;
+                candidates.push(reduced);
+            }
+        }
+    }
+    candidates
+}
+
+/// Runs the property on generated values, returning the number of passed cases or a shrunk failure.
+pub fn check<T>(
+    config: &Config,
+    generate: impl Fn(&mut Gen) -> T,
+    property: impl Fn(&T) -> Result<(), String>,
+) -> Result<usize, Failure<T>> {
+    let mut seeds = Rng::new(config.seed);
+    for case in 0..config.cases {
+        let size = 1 + case * config.max_size.saturating_sub(1) / config.cases.max(1);
+        let mut g = Gen::new(seeds.next_u64(), size);
+        let value = generate(&mut g);
+        if let Err(message) = property(&value) {
+            let mut failure = Failure { value, message, seed: config.seed, case, shrinks: 0 };
+            let mut choices = g.choices;
+            let mut replays = 0;
+            'shrink: while replays < config.max_shrinks {
+                for candidate in candidates(&choices) {
+                    replays += 1;
+                    if replays > config.max_shrinks {
+                        break 'shrink;
+                    }
+                    let mut replay = Gen::replay(candidate, size);
+                    let value = generate(&mut replay);
+                    if simpler(&replay.choices, &choices) {
+                        if let Err(message) = property(&value) {
+                            choices = replay.choices;
+                            failure = Failure { value, message, shrinks: failure.shrinks + 1, ..failure };
+                            continue 'shrink;
+                        }
+                    }
+                }
+                break;
+            }
+            return Err(failure);
+        }
+    }
+    Ok(config.cases)
+}
+
+/// Checks a property with the default configuration and panics with the shrunk counterexample if it fails.
+pub fn assert_property<T: Debug>(
+    name: &str,
+    generate: impl Fn(&mut Gen) -> T,
+    property: impl Fn(&T) -> Result<(), String>,
+) {
+    if let Err(failure) = check(&Config::default(), generate, property) {
+        panic!("Property '{}' does not hold. {}", name, failure);
+    }
+}
+
+/// Fails with a message showing both values unless they are equal.
+pub fn ensure_eq<V: PartialEq + Debug>(left: V, right: V, what: &str) -> Result<(), String> {
+    if left == right {
+        Ok(())
+    } else {
+        Err(format!("{} differ: {:?} != {:?}", what, left, right))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passing() {
+        let config = Config::default().cases(50);
+        let result = check(&config, |g| g.vec(0..=g.size(), |g| g.range(-5..=5)), |v| {
+            ensure_eq(v.iter().rev().rev().count(), v.len(), "lengths")
+        });
+        assert_eq!(result, Ok(50));
+    }
+
+    #[test]
+    fn test_shrinking() {
+        // Fails for any list containing a value of at least 50, the simplest such list is [50]
+        let failure = check(&Config::default(),
+                            |g| g.vec(0..=g.size(), |g| g.range(0..=1000)),
+                            |v| match v.iter().find(|x| **x >= 50) {
+                                Some(x) => Err(format!("{} is too large", x)),
+                                None => Ok(()),
+                            }).unwrap_err();
+        assert_eq!(failure.value, vec![50]);
+        assert_eq!(failure.message, "50 is too large");
+        assert!(failure.shrinks > 0);
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut g = Gen::replay(vec![7, 1, 99], 3);
+        assert_eq!(g.below(5), 4);
+        assert!(g.bool());
+        assert_eq!(g.choose(&['a', 'b', 'c']), Some(&'c'));
+        assert_eq!(g.choose::<char>(&[]), None);
+        // Past the end of the tape everything is minimal
+        assert_eq!(g.range(3..=9), 3);
+        assert!(!g.chance(50));
+        assert_eq!(g.choices(), &[4, 1, 2, 0, 0]);
+        assert_eq!(g.scaled(), 2..=3);
+        assert_eq!(Gen::new(0, 0).scaled(), 1..=1);
+    }
+
+    #[test]
+    fn test_wide_ranges() {
+        let mut g = Gen::new(7, 10);
+        for _ in 0..100 {
+            g.range(i64::MIN..=i64::MAX);
+            g.usize(0..=usize::MAX);
+            assert!(g.range(-1..=i64::MAX) >= -1);
+        }
+        let mut g = Gen::replay(vec![u64::MAX, u64::MAX, 0], 3);
+        assert_eq!(g.range(i64::MIN..=i64::MAX), i64::MAX);
+        assert_eq!(g.range(i64::MIN..=0), 0);
+        assert_eq!(g.usize(1..=usize::MAX), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Property 'small' does not hold")]
+    fn test_assert_property() {
+        assert_property("small", |g| g.usize(0..=100), |v| if *v < 10 { Ok(()) } else { Err("too large".into()) });
+    }
+}
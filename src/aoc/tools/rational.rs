@@ -0,0 +1,333 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+/// An exact fraction, always normalised: the denominator is positive and shares no factor with the numerator.
+/// The operators panic if a value overflows `i128`, the `checked_` methods return `None` instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numer: i128,
+    denom: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { numer: 0, denom: 1 };
+    pub const ONE: Rational = Rational { numer: 1, denom: 1 };
+
+    /// Panics if the denominator is zero or the normalised fraction does not fit, like `1/i128::MIN`.
+    pub fn new(numer: i128, denom: i128) -> Rational {
+        assert_ne!(denom, 0, "Denominator of {}/{} is zero", numer, denom);
+        Rational::checked_new(numer, denom)
+            .unwrap_or_else(|| panic!("Overflow normalising {}/{}", numer, denom))
+    }
+
+    /// `None` if the denominator is zero or the normalised fraction does not fit.
+    pub fn checked_new(numer: i128, denom: i128) -> Option<Rational> {
+        if denom == 0 {
+            return None;
+        }
+        // Normalise the magnitudes, as the sign change may not fit for `i128::MIN`
+        let g = gcd(numer, denom);
+        let (n, d) = (numer.unsigned_abs() / g, denom.unsigned_abs() / g);
+        let numer = match (numer < 0) != (denom < 0) {
+            true => 0i128.checked_sub_unsigned(n)?,
+            false => i128::try_from(n).ok()?,
+        };
+        Some(Rational { numer, denom: i128::try_from(d).ok()? })
+    }
+
+    pub const fn from_integer(n: i128) -> Rational {
+        Rational { numer: n, denom: 1 }
+    }
+
+    pub fn numer(&self) -> i128 {
+        self.numer
+    }
+
+    pub fn denom(&self) -> i128 {
+        self.denom
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == 1
+    }
+
+    /// The value as integer, `None` if it is a proper fraction.
+    pub fn to_integer(&self) -> Option<i128> {
+        self.is_integer().then_some(self.numer)
+    }
+
+    /// Panics for `i128::MIN`, whose magnitude does not fit.
+    pub fn abs(&self) -> Rational {
+        self.checked_abs().unwrap_or_else(|| panic!("Overflow computing |{}|", self))
+    }
+
+    /// Panics for zero.
+    pub fn recip(&self) -> Rational {
+        Rational::new(self.denom, self.numer)
+    }
+
+    /// `None` for zero and if the reciprocal does not fit, like for `1/i128::MIN`.
+    pub fn checked_recip(&self) -> Option<Rational> {
+        Rational::checked_new(self.denom, self.numer)
+    }
+}
+
+/// Greatest common divisor of the magnitudes, which fits even for `i128::MIN`. `gcd(0, 0)` is 0.
+fn gcd(a: i128, b: i128) -> u128 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl From<i32> for Rational {
+    fn from(n: i32) -> Self {
+        Rational::from_integer(n as i128)
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self {
+        Rational::from_integer(n as i128)
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self {
+        Rational::from_integer(n)
+    }
+}
+
+impl Rational {
+    pub fn checked_add(self, other: Rational) -> Option<Rational> {
+        // Working over the lcm of the denominators keeps intermediate values small,
+        // the gcd of positive denominators fits
+        let g = gcd(self.denom, other.denom) as i128;
+        let numer = self.numer.checked_mul(other.denom / g)?
+            .checked_add(other.numer.checked_mul(self.denom / g)?)?;
+        Rational::checked_new(numer, (self.denom / g).checked_mul(other.denom)?)
+    }
+
+    pub fn checked_sub(self, other: Rational) -> Option<Rational> {
+        self.checked_add(other.checked_neg()?)
+    }
+
+    pub fn checked_mul(self, other: Rational) -> Option<Rational> {
+        // Cancel crosswise first so the products only overflow if the result does,
+        // each gcd divides a positive denominator so it fits
+        let g1 = gcd(self.numer, other.denom) as i128;
+        let g2 = gcd(other.numer, self.denom) as i128;
+        let numer = (self.numer / g1).checked_mul(other.numer / g2)?;
+        let denom = (self.denom / g2).checked_mul(other.denom / g1)?;
+        Rational::checked_new(numer, denom)
+    }
+
+    /// `None` on overflow and when dividing by zero.
+    pub fn checked_div(self, other: Rational) -> Option<Rational> {
+        self.checked_mul(other.checked_recip()?)
+    }
+
+    pub fn checked_neg(self) -> Option<Rational> {
+        Some(Rational { numer: self.numer.checked_neg()?, denom: self.denom })
+    }
+
+    pub fn checked_abs(self) -> Option<Rational> {
+        Some(Rational { numer: self.numer.checked_abs()?, denom: self.denom })
+    }
+}
+
+fn overflow(op: &str, a: Rational, b: Rational) -> ! {
+    panic!("Overflow computing {} {} {}", a, op, b)
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, other: Rational) -> Rational {
+        self.checked_add(other).unwrap_or_else(|| overflow("+", self, other))
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, other: Rational) -> Rational {
+        self.checked_sub(other).unwrap_or_else(|| overflow("-", self, other))
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, other: Rational) -> Rational {
+        self.checked_mul(other).unwrap_or_else(|| overflow("*", self, other))
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    /// Panics when dividing by zero.
+    fn div(self, other: Rational) -> Rational {
+        self.checked_mul(other.recip()).unwrap_or_else(|| overflow("/", self, other))
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        self.checked_neg().unwrap_or_else(|| panic!("Overflow computing -{}", self))
+    }
+}
+
+impl Ord for Rational {
+    /// Compares the integer parts and then the reciprocals of the remainders, like continued fractions,
+    /// as subtracting or cross-multiplying may overflow.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (mut a, mut b) = (self.numer, self.denom);
+        let (mut c, mut d) = (other.numer, other.denom);
+        let mut reversed = false;
+        loop {
+            let ordering = match a.div_euclid(b).cmp(&c.div_euclid(d)) {
+                Ordering::Equal => {
+                    let (r1, r2) = (a.rem_euclid(b), c.rem_euclid(d));
+                    if r1 == 0 || r2 == 0 {
+                        r1.cmp(&r2)
+                    } else {
+                        // r1 / b < r2 / d exactly if b / r1 > d / r2
+                        (a, b, c, d) = (b, r1, d, r2);
+                        reversed = !reversed;
+                        continue;
+                    }
+                }
+                ordering => ordering,
+            };
+            return if reversed { ordering.reverse() } else { ordering };
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+impl FromStr for Rational {
+    type Err = String;
+
+    /// Parses `n` or `n/d`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid rational '{}'", s);
+        let (numer, denom) = s.split_once('/').unwrap_or((s, "1"));
+        let numer = numer.trim().parse::<i128>().map_err(|_| invalid())?;
+        let denom = denom.trim().parse::<i128>().map_err(|_| invalid())?;
+        Rational::checked_new(numer, denom).ok_or_else(invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(n: i128, d: i128) -> Rational {
+        Rational::new(n, d)
+    }
+
+    #[test]
+    fn test_normalise() {
+        assert_eq!(r(2, 4), r(1, 2));
+        assert_eq!(r(3, -6), r(-1, 2));
+        assert_eq!((r(-1, 2).numer(), r(-1, 2).denom()), (-1, 2));
+        assert_eq!(r(0, -5), Rational::ZERO);
+        assert_eq!(r(6, 3).to_integer(), Some(2));
+        assert_eq!(r(1, 3).to_integer(), None);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(r(1, 2) + r(1, 3), r(5, 6));
+        assert_eq!(r(1, 2) - r(1, 3), r(1, 6));
+        assert_eq!(r(2, 3) * r(3, 4), r(1, 2));
+        assert_eq!(r(2, 3) / r(4, 9), r(3, 2));
+        assert_eq!(-r(2, 3), r(-2, 3));
+        assert!(r(1, 3) < r(1, 2));
+        assert!(r(-1, 2) < r(-1, 3));
+        // Cancelling first keeps large but representable products working
+        let big = r(i128::MAX / 3, 7);
+        assert_eq!(big * r(7, i128::MAX / 3), Rational::ONE);
+    }
+
+    #[test]
+    fn test_checked() {
+        assert_eq!(r(1, 2).checked_add(r(1, 2)), Some(Rational::ONE));
+        assert_eq!(Rational::from_integer(i128::MAX).checked_add(Rational::ONE), None);
+        assert_eq!(Rational::from_integer(i128::MIN).checked_sub(Rational::ONE), None);
+        assert_eq!(r(1, 3).checked_div(Rational::ZERO), None);
+        assert_eq!(r(i128::MAX, 2).checked_mul(r(4, 3)), None);
+    }
+
+    #[test]
+    fn test_cmp_matches_cross_multiplying() {
+        for (n1, d1, n2, d2) in (-6..=6).flat_map(|n1| (1..=4).map(move |d1| (n1, d1)))
+            .flat_map(|(n1, d1)| (-6..=6).flat_map(move |n2| (1..=4).map(move |d2| (n1, d1, n2, d2)))) {
+            assert_eq!(r(n1, d1).cmp(&r(n2, d2)), (n1 * d2).cmp(&(n2 * d1)), "{}/{} and {}/{}", n1, d1, n2, d2);
+        }
+    }
+
+    #[test]
+    fn test_extremes() {
+        let (max, min) = (Rational::from_integer(i128::MAX), Rational::from_integer(i128::MIN));
+        assert_eq!(max.cmp(&Rational::from_integer(-5)), Ordering::Greater);
+        assert_eq!(min.cmp(&max), Ordering::Less);
+        assert!(r(i128::MAX, i128::MAX - 1) < r(i128::MAX - 1, i128::MAX - 2));
+        assert!(r(i128::MAX - 1, i128::MAX) > r(i128::MAX - 2, i128::MAX - 1));
+        assert!(r(-7, 3) < r(-9, 4));
+        assert_eq!(min.checked_add(Rational::ZERO), Some(min));
+        assert_eq!(min.checked_mul(r(1, 2)), Some(r(i128::MIN / 2, 1)));
+        assert_eq!(min.checked_neg(), None);
+        assert_eq!(min.checked_abs(), None);
+        assert_eq!(r(-3, 4).checked_abs(), Some(r(3, 4)));
+        assert_eq!(min.checked_recip(), None);
+        assert_eq!(Rational::ONE.checked_div(min), None);
+        assert_eq!(Rational::checked_new(i128::MIN, i128::MIN), Some(Rational::ONE));
+        assert_eq!(Rational::checked_new(-2, i128::MIN), Some(r(1, 1 << 126)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow computing |-170141183460469231731687303715884105728|")]
+    fn test_abs_overflow() {
+        Rational::from_integer(i128::MIN).abs();
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow")]
+    fn test_overflow() {
+        let _ = Rational::from_integer(i128::MAX) + Rational::ONE;
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!("-6/4".parse::<Rational>(), Ok(r(-3, 2)));
+        assert_eq!("7".parse::<Rational>(), Ok(Rational::from_integer(7)));
+        assert!("1/0".parse::<Rational>().is_err());
+        assert_eq!("1/-170141183460469231731687303715884105728".parse::<Rational>(),
+                   Err("Invalid rational '1/-170141183460469231731687303715884105728'".to_string()));
+        assert_eq!(r(-3, 2).to_string(), "-3/2");
+        assert_eq!(r(4, 2).to_string(), "2");
+    }
+}
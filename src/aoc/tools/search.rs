@@ -0,0 +1,197 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+/// The outcome of a graph search.
+/// Holds the cost to every state reached, enough information to reconstruct paths and a few stats.
+#[derive(Debug, Clone)]
+pub struct SearchResult<S> {
+    /// Cost of the cheapest known path from the start to each reached state
+    pub distances: HashMap<S, u64>,
+    /// The first state matching the goal predicate, if any was reached
+    pub goal: Option<S>,
+    /// Number of states taken from the frontier and expanded
+    pub visited: usize,
+    parents: HashMap<S, S>,
+}
+
+impl<S> SearchResult<S>
+    where S: Clone + Eq + Hash {
+    fn new(start: S) -> Self {
+        SearchResult {
+            distances: HashMap::from([(start, 0)]),
+            goal: None,
+            visited: 0,
+            parents: HashMap::new(),
+        }
+    }
+
+    /// Cost of the path to the goal.
+    pub fn cost(&self) -> Option<u64> {
+        self.goal.as_ref().and_then(|g| self.distances.get(g)).copied()
+    }
+
+    /// The states from the start up to and including the goal.
+    pub fn path(&self) -> Option<Vec<S>> {
+        self.goal.as_ref().and_then(|g| self.path_to(g))
+    }
+
+    /// The states from the start up to and including the given state, if it was reached.
+    pub fn path_to(&self, state: &S) -> Option<Vec<S>> {
+        if !self.distances.contains_key(state) {
+            return None;
+        }
+        let mut path = vec![state.clone()];
+        while let Some(parent) = self.parents.get(path.last().unwrap()) {
+            path.push(parent.clone());
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Breadth-first search over an implicit graph where every step costs one.
+/// Stops at the first state matching the goal, pass `|_| false` to explore everything reachable.
+pub fn bfs<S, I>(start: S, mut successors: impl FnMut(&S) -> I, mut goal: impl FnMut(&S) -> bool) -> SearchResult<S>
+    where S: Clone + Eq + Hash, I: IntoIterator<Item=S> {
+    let mut result = SearchResult::new(start.clone());
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(state) = queue.pop_front() {
+        result.visited += 1;
+        if goal(&state) {
+            result.goal = Some(state);
+            break;
+        }
+        let distance = result.distances[&state];
+        for next in successors(&state) {
+            if result.distances.contains_key(&next) {
+                continue;
+            }
+            result.distances.insert(next.clone(), distance + 1);
+            result.parents.insert(next.clone(), state.clone());
+            queue.push_back(next);
+        }
+    }
+    result
+}
+
+/// Dijkstra's algorithm over an implicit graph, successors are returned together with the step cost.
+pub fn dijkstra<S, I>(start: S, successors: impl FnMut(&S) -> I, goal: impl FnMut(&S) -> bool) -> SearchResult<S>
+    where S: Clone + Eq + Hash, I: IntoIterator<Item=(S, u64)> {
+    astar(start, successors, |_| 0, goal)
+}
+
+/// A* search over an implicit graph.
+/// The heuristic estimates the remaining cost to the goal and must never overestimate it.
+pub fn astar<S, I>(start: S,
+                   mut successors: impl FnMut(&S) -> I,
+                   mut heuristic: impl FnMut(&S) -> u64,
+                   mut goal: impl FnMut(&S) -> bool) -> SearchResult<S>
+    where S: Clone + Eq + Hash, I: IntoIterator<Item=(S, u64)> {
+    let mut result = SearchResult::new(start.clone());
+
+    // States are stored separately so the heap does not require them to be ordered
+    let mut states = vec![start.clone()];
+    let mut heap = BinaryHeap::from([Reverse((heuristic(&start), 0u64, 0usize))]);
+
+    while let Some(Reverse((_, distance, index))) = heap.pop() {
+        let state = states[index].clone();
+        // Skip stale entries, a cheaper path to this state was found after it was queued
+        if result.distances.get(&state).is_some_and(|d| *d < distance) {
+            continue;
+        }
+        result.visited += 1;
+        if goal(&state) {
+            result.goal = Some(state);
+            break;
+        }
+        for (next, cost) in successors(&state) {
+            let next_distance = distance + cost;
+            if result.distances.get(&next).is_some_and(|d| *d <= next_distance) {
+                continue;
+            }
+            result.distances.insert(next.clone(), next_distance);
+            result.parents.insert(next.clone(), state.clone());
+            heap.push(Reverse((next_distance + heuristic(&next), next_distance, states.len())));
+            states.push(next);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aoc::tools::{Grid, Point};
+
+    use super::*;
+
+    const MAZE: &str = r#"
+    S..#....
+    .#.#.##.
+    .#...#..
+    .####.#.
+    ......#E
+    "#;
+
+    fn maze() -> (Grid<char>, Point, Point) {
+        let grid = Grid::parse(MAZE, Some).unwrap();
+        let start = grid.find_all(|c| *c == 'S')[0];
+        let end = grid.find_all(|c| *c == 'E')[0];
+        (grid, start, end)
+    }
+
+    #[test]
+    fn test_bfs() {
+        let (grid, start, end) = maze();
+        let result = bfs(start,
+                         |p| grid.neighbours4(*p).filter(|n| grid[*n] != '#').collect::<Vec<_>>(),
+                         |p| *p == end);
+
+        assert_eq!(result.cost(), Some(15));
+        let path = result.path().unwrap();
+        assert_eq!(path.len(), 16);
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&end));
+        assert!(path.windows(2).all(|w| w[0].manhattan(w[1]) == 1));
+    }
+
+    #[test]
+    fn test_bfs_unreachable() {
+        let result = bfs(0, |n| if *n < 5 { vec![n + 1] } else { vec![] }, |n| *n == 10);
+        assert_eq!(result.goal, None);
+        assert_eq!(result.cost(), None);
+        assert_eq!(result.distances.len(), 6);
+        assert_eq!(result.visited, 6);
+        assert_eq!(result.path_to(&5), Some(vec![0, 1, 2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        // The direct edge is more expensive than the detour
+        let edges: HashMap<char, Vec<(char, u64)>> = HashMap::from([
+            ('a', vec![('b', 7), ('c', 2)]),
+            ('c', vec![('d', 1)]),
+            ('d', vec![('b', 1)]),
+            ('b', vec![('e', 3)]),
+        ]);
+        let result = dijkstra('a', |s| edges.get(s).cloned().unwrap_or_default(), |s| *s == 'e');
+        assert_eq!(result.cost(), Some(7));
+        assert_eq!(result.path(), Some(vec!['a', 'c', 'd', 'b', 'e']));
+    }
+
+    #[test]
+    fn test_astar_matches_dijkstra() {
+        let (grid, start, end) = maze();
+        let successors = |p: &Point| grid.neighbours4(*p)
+            .filter(|n| grid[*n] != '#')
+            .map(|n| (n, 1))
+            .collect::<Vec<_>>();
+
+        let plain = dijkstra(start, successors, |p| *p == end);
+        let guided = astar(start, successors, |p| p.manhattan(end), |p| *p == end);
+        assert_eq!(plain.cost(), Some(15));
+        assert_eq!(guided.cost(), plain.cost());
+        assert!(guided.visited <= plain.visited);
+    }
+}
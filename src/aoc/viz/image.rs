@@ -0,0 +1,250 @@
+use std::fmt::{Display, Formatter};
+
+use crate::aoc::tools::Point;
+use crate::aoc::viz::{box_directions, Canvas, Colour};
+
+/// A 24 bit colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// What terminals show for text without a colour.
+    pub const FOREGROUND: Rgb = Rgb(204, 204, 204);
+    pub const BACKGROUND: Rgb = Rgb(30, 30, 30);
+
+    /// Mixes in another colour, `share` of 0 keeps this one and 1 takes the other.
+    pub fn blend(&self, other: Rgb, share: f64) -> Rgb {
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * share).round() as u8;
+        Rgb(mix(self.0, other.0), mix(self.1, other.1), mix(self.2, other.2))
+    }
+}
+
+impl Display for Rgb {
+    /// Hex notation as used by SVG and HTML.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+impl Colour {
+    /// The colour in the default palette of common terminals.
+    pub fn rgb(&self) -> Rgb {
+        match self {
+            Colour::Red => Rgb(205, 49, 49),
+            Colour::Green => Rgb(13, 188, 121),
+            Colour::Yellow => Rgb(229, 229, 16),
+            Colour::Blue => Rgb(36, 114, 200),
+            Colour::Magenta => Rgb(188, 63, 188),
+            Colour::Cyan => Rgb(17, 168, 205),
+            Colour::Grey => Rgb(118, 118, 118),
+        }
+    }
+}
+
+/// A raster image with pixels stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Image {
+        Image { width, height, pixels: vec![background; width * height] }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    /// Fills a rectangle, clipped to the image.
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, colour: Rgb) {
+        for row in y..(y + height).min(self.height) {
+            for col in x..(x + width).min(self.width) {
+                self.pixels[row * self.width + col] = colour;
+            }
+        }
+    }
+
+    fn bytes(&self) -> impl Iterator<Item=u8> + '_ {
+        self.pixels.iter().flat_map(|p| [p.0, p.1, p.2])
+    }
+
+    /// Binary PPM, the simplest format most image tools read.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.extend(self.bytes());
+        out
+    }
+
+    /// PNG with uncompressed deflate blocks, which keeps the encoder small.
+    pub fn to_png(&self) -> Vec<u8> {
+        // Every row starts with filter type 0, no filtering
+        let row_len = self.width * 3;
+        let raw: Vec<u8> = self.bytes()
+            .collect::<Vec<u8>>()
+            .chunks(row_len.max(1))
+            .flat_map(|row| std::iter::once(0).chain(row.iter().copied()))
+            .collect();
+
+        let mut header = Vec::new();
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // 8 bits per channel, truecolour, default compression, filtering and no interlacing
+        header.extend([8, 2, 0, 0, 0]);
+
+        let mut out = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        write_chunk(&mut out, b"IHDR", &header);
+        write_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+        write_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+/// A zlib stream of stored deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 65535;
+    // Deflate with a 32K window, no preset dictionary, header checksum making it divisible by 31
+    let mut out = vec![0x78, 0x01];
+    let blocks: Vec<&[u8]> = if data.is_empty() { vec![&[]] } else { data.chunks(MAX_BLOCK).collect() };
+    for (i, block) in blocks.iter().enumerate() {
+        out.push((i == blocks.len() - 1) as u8);
+        let len = block.len() as u16;
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend(*block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
+
+impl Canvas {
+    /// Rasterises the canvas with square cells of `cell` pixels, at least one.
+    /// Paths become lines, shading fills the cell and other characters a smaller square, ground stays empty.
+    pub fn to_image(&self, cell: usize) -> Image {
+        let cell = cell.max(1);
+        let mut image = Image::new(self.width() * cell, self.height() * cell, Rgb::BACKGROUND);
+        let thickness = (cell / 4).max(1);
+        let centre = (cell - thickness) / 2;
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let (c, style) = self.get(Point::from((x, y))).unwrap();
+                let (left, top) = (x * cell, y * cell);
+                let background = style.bg.map(|c| c.rgb()).unwrap_or(Rgb::BACKGROUND);
+                let foreground = style.fg.map(|c| c.rgb()).unwrap_or(Rgb::FOREGROUND);
+                image.fill_rect(left, top, cell, cell, background);
+                if let Some(directions) = box_directions(c) {
+                    image.fill_rect(left + centre, top + centre, thickness, thickness, foreground);
+                    for d in directions {
+                        let v = d.vector();
+                        // From the centre to the edge of the cell
+                        let (x0, x1) = match v.x { 1 => (centre, cell), -1 => (0, centre + thickness), _ => (centre, centre + thickness) };
+                        let (y0, y1) = match v.y { 1 => (centre, cell), -1 => (0, centre + thickness), _ => (centre, centre + thickness) };
+                        image.fill_rect(left + x0, top + y0, x1 - x0, y1 - y0, foreground);
+                    }
+                } else {
+                    match c {
+                        '.' | ' ' => {}
+                        '░' => image.fill_rect(left, top, cell, cell, background.blend(foreground, 0.5)),
+                        _ => image.fill_rect(left + cell / 4, top + cell / 4, cell - cell / 2, cell - cell / 2, foreground),
+                    }
+                }
+            }
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aoc::tools::Grid;
+    use crate::aoc::viz::{ImageFormat, Style};
+
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        // Reference values of the zlib implementation
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_ppm() {
+        let mut image = Image::new(2, 1, Rgb(0, 0, 0));
+        image.fill_rect(1, 0, 5, 5, Rgb(255, 0, 16));
+        assert_eq!(image.to_ppm(), b"P6\n2 1\n255\n\x00\x00\x00\xff\x00\x10".to_vec());
+    }
+
+    #[test]
+    fn test_png() {
+        let png = Image::new(3, 2, Rgb(1, 2, 3)).to_png();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+        // Two rows of a filter byte and three pixels in a single stored block
+        let idat = &png[33..];
+        assert_eq!(&idat[..4], &(2 + 5 + 20 + 4u32).to_be_bytes());
+        assert_eq!(&idat[4..11], b"IDAT\x78\x01\x01");
+        assert_eq!(&idat[11..15], &[20, 0, !20, 0xff]);
+    }
+
+    #[test]
+    fn test_to_image() {
+        let grid = Grid::parse(".#", Some).unwrap();
+        let canvas = Canvas::new(&grid, |c| *c).highlight([Point::new(1, 0)], Style::fg(Colour::Red));
+        let image = canvas.to_image(4);
+        assert_eq!((image.width(), image.height()), (8, 4));
+        assert_eq!(image.get(1, 1), Some(Rgb::BACKGROUND));
+        assert_eq!(image.get(5, 1), Some(Colour::Red.rgb()));
+        assert_eq!(image.get(4, 0), Some(Rgb::BACKGROUND));
+    }
+
+    #[test]
+    fn test_zero_cell_size() {
+        let grid = Grid::parse("-|\n.#", Some).unwrap();
+        let canvas = Canvas::new(&grid, |c| *c);
+        assert_eq!(canvas.to_image(0).width(), 2);
+        assert!(!canvas.encode(ImageFormat::Svg, 0).is_empty());
+    }
+}
@@ -0,0 +1,316 @@
+use std::fmt::Write;
+use std::io::IsTerminal;
+use std::path::Path;
+
+use crate::aoc::tools::{Direction, Grid, Point};
+
+pub mod image;
+pub mod record;
+pub mod svg;
+
+/// The file formats a canvas can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    /// Picks the format by file extension, `None` if it is not known.
+    pub fn from_path(path: &Path) -> Option<ImageFormat> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "ppm" => Some(ImageFormat::Ppm),
+            "png" => Some(ImageFormat::Png),
+            "svg" => Some(ImageFormat::Svg),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
+        }
+    }
+}
+
+/// The standard terminal colours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colour {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Grey,
+}
+
+impl Colour {
+    /// The ANSI code for the foreground, backgrounds are 10 higher.
+    fn code(&self) -> u8 {
+        match self {
+            Colour::Red => 31,
+            Colour::Green => 32,
+            Colour::Yellow => 33,
+            Colour::Blue => 34,
+            Colour::Magenta => 35,
+            Colour::Cyan => 36,
+            Colour::Grey => 90,
+        }
+    }
+}
+
+/// How a cell is drawn. Unset fields leave whatever lower layers painted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    pub fg: Option<Colour>,
+    pub bg: Option<Colour>,
+    pub bold: bool,
+    /// Replaces the character of the cell, so the layer shows without colours as well
+    pub fill: Option<char>,
+}
+
+impl Style {
+    pub fn fg(colour: Colour) -> Style {
+        Style { fg: Some(colour), ..Style::default() }
+    }
+
+    pub fn bg(colour: Colour) -> Style {
+        Style { bg: Some(colour), ..Style::default() }
+    }
+
+    pub fn bold(self) -> Style {
+        Style { bold: true, ..self }
+    }
+
+    pub fn fill(self, c: char) -> Style {
+        Style { fill: Some(c), ..self }
+    }
+
+    /// Paints this style over another one.
+    fn over(self, below: Style) -> Style {
+        Style {
+            fg: self.fg.or(below.fg),
+            bg: self.bg.or(below.bg),
+            bold: self.bold || below.bold,
+            fill: self.fill.or(below.fill),
+        }
+    }
+
+    fn escape(&self) -> String {
+        let mut codes = Vec::new();
+        if self.bold {
+            codes.push(1);
+        }
+        codes.extend(self.fg.map(|c| c.code()));
+        codes.extend(self.bg.map(|c| c.code() + 10));
+        let codes: Vec<String> = codes.iter().map(|c| c.to_string()).collect();
+        format!("\x1b[{}m", codes.join(";"))
+    }
+}
+
+/// Whether stdout takes colours: a terminal, and `NO_COLOR` is not set.
+pub fn colour_enabled() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+/// The box-drawing character joining the given orthogonal directions, a dot for none.
+pub fn box_char(directions: &[Direction]) -> char {
+    let has = |d: Direction| directions.contains(&d);
+    match (has(Direction::N), has(Direction::E), has(Direction::S), has(Direction::W)) {
+        (true, false, true, false) => '│',
+        (false, true, false, true) => '─',
+        (false, true, true, false) => '┌',
+        (false, false, true, true) => '┐',
+        (true, true, false, false) => '└',
+        (true, false, false, true) => '┘',
+        (true, true, true, false) => '├',
+        (true, false, true, true) => '┤',
+        (false, true, true, true) => '┬',
+        (true, true, false, true) => '┴',
+        (true, true, true, true) => '┼',
+        (true, false, false, false) => '╵',
+        (false, true, false, false) => '╶',
+        (false, false, true, false) => '╷',
+        (false, false, false, true) => '╴',
+        (false, false, false, false) => '•',
+    }
+}
+
+/// The directions a box-drawing character from `box_char` joins, `None` for other characters.
+pub fn box_directions(c: char) -> Option<Vec<Direction>> {
+    (0..16u8)
+        .map(|bits| Direction::CARDINAL.into_iter()
+            .enumerate()
+            .filter(|(i, _)| bits & (1 << i) != 0)
+            .map(|(_, d)| d)
+            .collect::<Vec<Direction>>())
+        .find(|directions| box_char(directions) == c)
+}
+
+/// The orthogonal direction leading from one point to its neighbour, `None` if they are not neighbours.
+fn step(from: Point, to: Point) -> Option<Direction> {
+    Direction::CARDINAL.into_iter().find(|d| from + d.vector() == to)
+}
+
+/// A grid prepared for the terminal, built from layers that paint over each other.
+///
+/// ```
+/// use aoc2023::aoc::tools::{Grid, Point};
+/// use aoc2023::aoc::viz::{Canvas, Colour, Style};
+///
+/// let grid = Grid::parse("...\n...", Some).unwrap();
+/// let path = [Point::new(0, 0), Point::new(1, 0), Point::new(1, 1), Point::new(2, 1)];
+/// let canvas = Canvas::new(&grid, |c| *c).path(&path, false, Style::fg(Colour::Yellow));
+/// assert_eq!(canvas.render(false), "╶┐.\n.└╴\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    cells: Grid<(char, Style)>,
+}
+
+impl Canvas {
+    /// Starts from a grid, drawing every cell as the given character.
+    pub fn new<T>(grid: &Grid<T>, f: impl Fn(&T) -> char) -> Canvas {
+        Canvas { cells: grid.map(|cell| (f(cell), Style::default())) }
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    /// The character and style of a cell after all layers.
+    pub fn get(&self, p: Point) -> Option<(char, Style)> {
+        self.cells.get(p).map(|(c, style)| (style.fill.unwrap_or(*c), *style))
+    }
+
+    fn paint(&mut self, p: Point, c: Option<char>, style: Style) {
+        if let Some(cell) = self.cells.get_mut(p) {
+            cell.0 = c.unwrap_or(cell.0);
+            cell.1 = style.over(cell.1);
+        }
+    }
+
+    /// Paints the points in a style, e.g. a set of visited cells or the galaxies of a universe.
+    /// Points outside the grid are skipped.
+    pub fn highlight(mut self, points: impl IntoIterator<Item=Point>, style: Style) -> Canvas {
+        for p in points {
+            self.paint(p, None, style);
+        }
+        self
+    }
+
+    /// Draws a path of orthogonal steps with box-drawing characters. A closed path joins its ends.
+    /// Consecutive points that are not neighbours leave a gap.
+    pub fn path(mut self, points: &[Point], closed: bool, style: Style) -> Canvas {
+        for (i, p) in points.iter().enumerate() {
+            let previous = match i {
+                0 if closed => points.last(),
+                0 => None,
+                _ => points.get(i - 1),
+            };
+            let next = match points.get(i + 1) {
+                None if closed => points.first(),
+                next => next,
+            };
+            let directions: Vec<Direction> = [previous, next].into_iter()
+                .flatten()
+                .filter_map(|q| step(*p, *q))
+                .collect();
+            self.paint(*p, Some(box_char(&directions)), style);
+            // A fill from a lower layer would hide the path
+            if let Some(cell) = self.cells.get_mut(*p) {
+                cell.1.fill = style.fill;
+            }
+        }
+        self
+    }
+
+    /// Encodes the canvas as a file of the given format, with square cells of `cell` pixels, at least one.
+    pub fn encode(&self, format: ImageFormat, cell: usize) -> Vec<u8> {
+        match format {
+            ImageFormat::Ppm => self.to_image(cell).to_ppm(),
+            ImageFormat::Png => self.to_image(cell).to_png(),
+            ImageFormat::Svg => self.to_svg(cell).into_bytes(),
+        }
+    }
+
+    /// Renders row by row, with ANSI colours if `colour` is set.
+    pub fn render(&self, colour: bool) -> String {
+        let mut out = String::new();
+        for row in self.cells.rows() {
+            let mut current = Style::default();
+            for (c, style) in row {
+                let shown = style.fill.unwrap_or(*c);
+                let style = Style { fill: None, ..*style };
+                if colour && style != current {
+                    out.push_str("\x1b[0m");
+                    if style != Style::default() {
+                        out.push_str(&style.escape());
+                    }
+                    current = style;
+                }
+                out.push(shown);
+            }
+            if colour && current != Style::default() {
+                out.push_str("\x1b[0m");
+            }
+            writeln!(out).unwrap();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dots(width: usize, height: usize) -> Grid<char> {
+        Grid::filled(width, height, '.')
+    }
+
+    #[test]
+    fn test_box_char() {
+        assert_eq!(box_char(&[Direction::S, Direction::E]), '┌');
+        assert_eq!(box_char(&[Direction::W, Direction::N]), '┘');
+        assert_eq!(box_char(&[Direction::N]), '╵');
+        assert_eq!(box_char(&[]), '•');
+    }
+
+    #[test]
+    fn test_box_directions() {
+        assert_eq!(box_directions('┌'), Some(vec![Direction::E, Direction::S]));
+        assert_eq!(box_directions('•'), Some(vec![]));
+        assert_eq!(box_directions('#'), None);
+        assert_eq!(ImageFormat::from_path(Path::new("out/day10.PNG")), Some(ImageFormat::Png));
+        assert_eq!(ImageFormat::from_path(Path::new("day10")), None);
+    }
+
+    #[test]
+    fn test_closed_path() {
+        let square = [Point::new(0, 0), Point::new(1, 0), Point::new(2, 0), Point::new(2, 1),
+            Point::new(2, 2), Point::new(1, 2), Point::new(0, 2), Point::new(0, 1)];
+        let canvas = Canvas::new(&dots(4, 3), |c| *c)
+            .highlight([Point::new(1, 1)], Style::default().fill('░'))
+            .path(&square, true, Style::default());
+        assert_eq!(canvas.render(false), "┌─┐.\n│░│.\n└─┘.\n");
+    }
+
+    #[test]
+    fn test_layers() {
+        let canvas = Canvas::new(&dots(2, 1), |c| *c)
+            .highlight([Point::new(0, 0), Point::new(1, 0)], Style::bg(Colour::Blue))
+            .highlight([Point::new(1, 0), Point::new(5, 5)], Style::fg(Colour::Red).bold().fill('#'));
+        assert_eq!(canvas.get(Point::new(1, 0)),
+                   Some(('#', Style { fg: Some(Colour::Red), bg: Some(Colour::Blue), bold: true, fill: Some('#') })));
+        assert_eq!(canvas.render(false), ".#\n");
+        assert_eq!(canvas.render(true), "\x1b[0m\x1b[44m.\x1b[0m\x1b[1;31;44m#\x1b[0m\n");
+    }
+}
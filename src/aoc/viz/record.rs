@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+
+use crate::aoc::viz::{Canvas, ImageFormat};
+
+/// Collects the frames of an animation while a solver runs.
+/// Solvers call `frame` on every step, only every n-th step is drawn and kept.
+///
+/// ```
+/// use aoc2023::aoc::tools::Grid;
+/// use aoc2023::aoc::viz::Canvas;
+/// use aoc2023::aoc::viz::record::Recorder;
+///
+/// let grid = Grid::filled(2, 2, '.');
+/// let mut recorder = Recorder::every(3);
+/// for _ in 0..7 {
+///     recorder.frame(|| Canvas::new(&grid, |c| *c));
+/// }
+/// assert_eq!(recorder.frames().len(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    every: usize,
+    steps: usize,
+    frames: Vec<Canvas>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Recorder::every(1)
+    }
+}
+
+impl Recorder {
+    /// Keeps every step.
+    pub fn new() -> Recorder {
+        Recorder::default()
+    }
+
+    /// Keeps the first step and then every `n`-th. Panics for zero.
+    pub fn every(n: usize) -> Recorder {
+        assert!(n > 0, "Cannot keep every 0th frame");
+        Recorder { every: n, steps: 0, frames: Vec::new() }
+    }
+
+    /// Records a step. The frame is only drawn if it is kept.
+    pub fn frame(&mut self, draw: impl FnOnce() -> Canvas) {
+        if self.steps.is_multiple_of(self.every) {
+            self.frames.push(draw());
+        }
+        self.steps += 1;
+    }
+
+    /// Keeps a frame regardless of the step, e.g. the final state.
+    pub fn keep(&mut self, canvas: Canvas) {
+        self.frames.push(canvas);
+    }
+
+    pub fn frames(&self) -> &[Canvas] {
+        &self.frames
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Writes the frames as `frame_00000.png` and so on into a directory, creating it if needed.
+    /// Returns the written files in order.
+    pub fn write_frames(&self, dir: &Path, format: ImageFormat, cell: usize) -> std::io::Result<Vec<PathBuf>> {
+        std::fs::create_dir_all(dir)?;
+        self.frames.iter()
+            .enumerate()
+            .map(|(i, canvas)| {
+                let path = dir.join(format!("frame_{:05}.{}", i, format.extension()));
+                std::fs::write(&path, canvas.encode(format, cell))?;
+                Ok(path)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aoc::tools::Grid;
+
+    use super::*;
+
+    #[test]
+    fn test_write_frames() {
+        let dir = std::env::temp_dir().join(format!("aoc-frames-{}", std::process::id()));
+        let grid = Grid::filled(3, 1, '#');
+        let mut recorder = Recorder::new();
+        recorder.frame(|| Canvas::new(&grid, |c| *c));
+        recorder.keep(Canvas::new(&grid, |_| '.'));
+
+        let files = recorder.write_frames(&dir, ImageFormat::Ppm, 2).unwrap();
+        assert_eq!(files, vec![dir.join("frame_00000.ppm"), dir.join("frame_00001.ppm")]);
+        let first = std::fs::read(&files[0]).unwrap();
+        assert!(first.starts_with(b"P6\n6 2\n255\n"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,83 @@
+use std::fmt::Write;
+
+use crate::aoc::tools::Point;
+use crate::aoc::viz::{box_directions, Canvas};
+use crate::aoc::viz::image::Rgb;
+
+fn escape(c: char) -> String {
+    match c {
+        '&' => "&amp;".to_string(),
+        '<' => "&lt;".to_string(),
+        '>' => "&gt;".to_string(),
+        c => c.to_string(),
+    }
+}
+
+impl Canvas {
+    /// Draws the canvas as SVG with square cells of `cell` units, at least one.
+    /// Paths become lines so they stay sharp when scaled, other characters are kept as text.
+    pub fn to_svg(&self, cell: usize) -> String {
+        let cell = cell.max(1);
+        let (width, height) = (self.width() * cell, self.height() * cell);
+        let half = cell as f64 / 2.0;
+        let mut out = String::new();
+        writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#, width, height).unwrap();
+        writeln!(out, r#"<rect width="100%" height="100%" fill="{}"/>"#, Rgb::BACKGROUND).unwrap();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let (c, style) = self.get(Point::from((x, y))).unwrap();
+                let (left, top) = (x * cell, y * cell);
+                let (cx, cy) = (left as f64 + half, top as f64 + half);
+                let foreground = style.fg.map(|c| c.rgb()).unwrap_or(Rgb::FOREGROUND);
+                if let Some(bg) = style.bg {
+                    writeln!(out, r#"<rect x="{}" y="{}" width="{2}" height="{2}" fill="{3}"/>"#, left, top, cell, bg.rgb()).unwrap();
+                }
+                if let Some(directions) = box_directions(c) {
+                    let mut d = String::new();
+                    for direction in &directions {
+                        let v = direction.vector();
+                        write!(d, "M{} {}L{} {}", cx, cy, cx + v.x as f64 * half, cy + v.y as f64 * half).unwrap();
+                    }
+                    if directions.is_empty() {
+                        write!(d, "M{} {}h0", cx, cy).unwrap();
+                    }
+                    writeln!(out, r#"<path d="{}" stroke="{}" stroke-width="{}" stroke-linecap="square"/>"#,
+                             d, foreground, (cell / 4).max(1)).unwrap();
+                } else {
+                    match c {
+                        '.' | ' ' => {}
+                        '░' => writeln!(out, r#"<rect x="{}" y="{}" width="{2}" height="{2}" fill="{3}" fill-opacity="0.5"/>"#,
+                                        left, top, cell, foreground).unwrap(),
+                        c => writeln!(out, r#"<text x="{}" y="{}" font-family="monospace" font-size="{}" text-anchor="middle" dominant-baseline="central" fill="{}"{}>{}</text>"#,
+                                      cx, cy, cell, foreground, if style.bold { r#" font-weight="bold""# } else { "" }, escape(c)).unwrap(),
+                    }
+                }
+            }
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aoc::tools::Grid;
+    use crate::aoc::viz::{Colour, Style};
+
+    use super::*;
+
+    #[test]
+    fn test_to_svg() {
+        let grid = Grid::parse(".<\n..", Some).unwrap();
+        let canvas = Canvas::new(&grid, |c| *c)
+            .highlight([Point::new(1, 0)], Style::fg(Colour::Red).bold())
+            .path(&[Point::new(0, 1), Point::new(1, 1)], false, Style::bg(Colour::Blue));
+        let svg = canvas.to_svg(10);
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="20" viewBox="0 0 20 20">"#));
+        assert!(svg.contains(r##"fill="#cd3131" font-weight="bold">&lt;</text>"##));
+        assert!(svg.contains(r##"<rect x="10" y="10" width="10" height="10" fill="#2472c8"/>"##));
+        assert!(svg.contains(r#"<path d="M5 15L10 15""#));
+        assert!(svg.contains(r#"<path d="M15 15L10 15""#));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
@@ -2,6 +2,7 @@ use crate::aoc::{
     Day,
     tools::{
         find_char, find_substrings, read_lines, rfind_char, rfind_substrings, string_to_digit,
+        prop::Gen,
     },
 };
 
@@ -13,6 +14,24 @@ impl Day1 {
     pub fn new(input: String) -> Day1 {
         Day1 { input }
     }
+
+    /// A random calibration document of letters, digits and spelled out digits.
+    /// Every line has at least one digit.
+    pub fn generate(g: &mut Gen) -> String {
+        const WORDS: [&str; 9] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+        let digit = |g: &mut Gen| char::from(b'1' + g.below(9) as u8);
+        let lines = g.vec(g.scaled(), |g| {
+            let mut line: String = g.vec(0..=6, |g| match g.below(3) {
+                0 => char::from(b'a' + g.below(26) as u8).to_string(),
+                1 => g.choose(&WORDS).unwrap().to_string(),
+                _ => digit(g).to_string(),
+            }).concat();
+            let at = g.usize(0..=line.len());
+            line.insert(at, digit(g));
+            line
+        });
+        lines.join("\n")
+    }
 }
 
 impl Day for Day1 {
@@ -21,8 +40,8 @@ impl Day for Day1 {
 
         let mut sum = 0;
         for l in lines.iter() {
-            let first = find_char(l, |c| c.is_numeric()).unwrap_or('0');
-            let last = rfind_char(l, |c| c.is_numeric()).unwrap_or('0');
+            let first = find_char(l, |c| c.is_ascii_digit()).unwrap_or('0');
+            let last = rfind_char(l, |c| c.is_ascii_digit()).unwrap_or('0');
             let s = format!("{}{}", first, last);
             sum += s.parse::<u32>().unwrap();
         }
@@ -57,6 +76,7 @@ impl Day for Day1 {
 #[cfg(test)]
 mod tests {
     use crate::aoc::Day;
+    use crate::aoc::tools::prop::{assert_property, ensure_eq};
 
     #[test]
     fn test_part_1() {
@@ -99,6 +119,23 @@ mod tests {
         assert_eq!(day1.part2(), "0");
     }
 
+    #[test]
+    fn test_other_numerals_are_not_digits() {
+        let day1 = super::Day1::new("²7x٣".to_string());
+        assert_eq!(day1.part1(), "77");
+    }
+
+    #[test]
+    fn test_words_only_matter_in_part2() {
+        // Without the letters, spelled out digits are gone and both parts agree with part 1 of the full document
+        assert_property("letters do not matter in part 1", super::Day1::generate, |input| {
+            let digits: String = input.chars().filter(|c| !c.is_alphabetic()).collect();
+            let part1 = super::Day1::new(input.clone()).part1();
+            ensure_eq(&super::Day1::new(digits.clone()).part2(), &part1, "part 2 without letters")?;
+            ensure_eq(&super::Day1::new(digits).part1(), &part1, "part 1 without letters")
+        });
+    }
+
     // Assuming each line contains exactly two digits or none
     // #[test]
     // fn test_one_digit() {
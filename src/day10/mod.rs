@@ -1,11 +1,18 @@
-use std::ops::{Add, Div, Mul, Sub};
-
-use crate::aoc::Day;
-use crate::aoc::tools::read_lines;
-use crate::day10::Dir::{E, N, S, W};
+use crate::aoc::{Context, Day};
+use crate::aoc::crosscheck::Alternative;
+use crate::aoc::runner::{Part, SolveError};
+use crate::aoc::tools::{Direction, Grid, Point};
+use crate::aoc::tools::bitset::VisitedMask;
+use crate::aoc::tools::Direction::{E, N, S, W};
+use crate::aoc::tools::geometry;
+use crate::aoc::tools::parse::{Field, ParseError};
+use crate::aoc::tools::prop::Gen;
+use crate::aoc::tools::search::bfs;
+use crate::aoc::viz::{Canvas, Colour, Style};
+use crate::aoc::viz::record::Recorder;
 
 #[derive(PartialEq, Clone, Debug)]
-enum Tile {
+pub enum Tile {
     NS,
     EW,
     NE,
@@ -16,170 +23,157 @@ enum Tile {
     Start,
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Dir {
-    N,
-    S,
-    E,
-    W,
-}
-
-impl Dir {
-    fn as_dir(&self) -> (i32, i32) {
-        match self {
-            N => (0, -1),
-            S => (0, 1),
-            E => (1, 0),
-            W => (-1, 0),
+impl Tile {
+    pub fn from_char(c: char) -> Option<Tile> {
+        match c {
+            '.' => Some(Tile::Ground),
+            '|' => Some(Tile::NS),
+            '-' => Some(Tile::EW),
+            'L' => Some(Tile::NE),
+            'J' => Some(Tile::NW),
+            '7' => Some(Tile::SW),
+            'F' => Some(Tile::SE),
+            'S' => Some(Tile::Start),
+            _ => None,
         }
     }
 
-    fn x(&self) -> i32 {
-        self.as_dir().0
+    /// The character the tile is parsed from.
+    pub fn symbol(&self) -> char {
+        match self {
+            Tile::Ground => '.',
+            Tile::NS => '|',
+            Tile::EW => '-',
+            Tile::NE => 'L',
+            Tile::NW => 'J',
+            Tile::SW => '7',
+            Tile::SE => 'F',
+            Tile::Start => 'S',
+        }
     }
 
-    fn y(&self) -> i32 {
-        self.as_dir().1
+    /// The directions a pipe connects to. The start may connect in any direction.
+    pub fn connections(&self) -> &'static [Direction] {
+        match self {
+            Tile::NS => &[N, S],
+            Tile::EW => &[E, W],
+            Tile::NE => &[N, E],
+            Tile::NW => &[N, W],
+            Tile::SW => &[S, W],
+            Tile::SE => &[S, E],
+            Tile::Ground => &[],
+            Tile::Start => &Direction::CARDINAL,
+        }
     }
 }
 
-struct Maze {
-    map: Vec<Vec<Tile>>,
-    start: (usize, usize),
+pub struct Maze {
+    pub map: Grid<Tile>,
+    pub start: Point,
 }
 
 impl Maze {
-    fn new(input: Vec<String>) -> Maze {
-        let mut map = Vec::new();
-        let mut start = (0, 0);
-
-        for (y, line) in input.into_iter().enumerate() {
-            let mut row = Vec::new();
-            for (x, c) in line.chars().enumerate() {
-                let tile = match c {
-                    '.' => Tile::Ground,
-                    '|' => Tile::NS,
-                    '-' => Tile::EW,
-                    'L' => Tile::NE,
-                    'J' => Tile::NW,
-                    '7' => Tile::SW,
-                    'F' => Tile::SE,
-                    'S' => Tile::Start,
-                    _ => panic!("Invalid character in input: {}", c),
-                };
-                if tile == Tile::Start {
-                    start = (x, y);
-                }
-                row.push(tile);
-            }
-            map.push(row);
+    pub fn new(input: &str) -> Maze {
+        Maze::parse(input).unwrap_or_else(|e| panic!("Invalid input: {}", e))
+    }
+
+    /// A rectangular map of pipes with exactly one start.
+    pub fn parse(input: &str) -> Result<Maze, ParseError> {
+        let field = Field::new(input);
+        let map = field.grid(Tile::from_char)?;
+        let starts = field.lines().into_iter()
+            .flat_map(|line| line.text.match_indices('S').map(move |(i, _)| line.slice(i..i + 1)))
+            .collect::<Vec<Field>>();
+        match starts[..] {
+            [_] => {}
+            [] => return Err(field.slice(input.len()..input.len()).error("Expected a start 'S', found none")),
+            [_, second, ..] => return Err(second.error("Expected a single start 'S'")),
         }
-        Maze {
+        let start = map.find_all(|t| *t == Tile::Start)[0];
+        Ok(Maze {
             map,
             start,
-        }
+        })
     }
 
-    fn find_start_dir(&self) -> Dir {
-        let north = &self.map[self.start.1.saturating_sub(1)][self.start.0];
-        let south = &self.map[self.start.1.add(1)][self.start.0];
-        let east = &self.map[self.start.1][self.start.0.add(1)];
-        let west = &self.map[self.start.1][self.start.0.saturating_sub(1)];
-
-        match north {
-            Tile::NS | Tile::SE | Tile::SW => Dir::N,
-            _ => match south {
-                Tile::NS | Tile::NE | Tile::NW => Dir::S,
-                _ => match east {
-                    Tile::EW | Tile::NW | Tile::SW => Dir::E,
-                    _ => match west {
-                        Tile::EW | Tile::NE | Tile::SE => Dir::W,
-                        _ => panic!("No valid start direction found for {:?}", self.start),
-                    }
-                }
+    /// The positions whose pipes connect to the pipe at the given position in both directions.
+    pub fn connected(&self, p: Point) -> Vec<Point> {
+        self.map[p].connections().iter()
+            .map(|d| (p + d.vector(), d.reverse()))
+            .filter(|(n, back)| self.map.get(*n).is_some_and(|t| t.connections().contains(back)))
+            .map(|(n, _)| n)
+            .collect()
+    }
+
+    /// The tiles of the loop in walking order from the start, `None` if the start is not on a loop.
+    /// Every pipe is entered from one end and left at the other, so the walk cannot run into a loop without
+    /// the start and ends within the grid.
+    pub fn find_loop(&self) -> Option<Vec<Point>> {
+        [N, S, E, W].into_iter().find_map(|dir| self.walk_from_start(dir))
+    }
+
+    fn walk_from_start(&self, mut dir: Direction) -> Option<Vec<Point>> {
+        let mut path = vec![self.start];
+        let mut position = self.start + dir.vector();
+        loop {
+            let tile = self.map.get(position)?;
+            if *tile == Tile::Start {
+                return Some(path);
             }
+            let connections = tile.connections();
+            if !connections.contains(&dir.reverse()) {
+                return None;
+            }
+            dir = *connections.iter().find(|d| **d != dir.reverse())?;
+            path.push(position);
+            position += dir.vector();
         }
     }
 
-    fn find_loop(&self) -> Vec<(usize, usize)> {
-        let mut dir = self.find_start_dir(); // Two possibilities
-        let mut x = (self.start.0 as i32 + dir.x()) as usize;
-        let mut y = (self.start.1 as i32 + dir.y()) as usize;
+    /// Marks every tile of the loop.
+    pub fn loop_mask(&self) -> Option<VisitedMask> {
+        let mut mask = VisitedMask::for_grid(&self.map);
+        for p in self.find_loop()? {
+            mask.insert(p);
+        }
+        Some(mask)
+    }
 
-        let mut _loop = vec![self.start];
+    /// Whether the pipe at the position leads north, for the start depending on its neighbours.
+    pub fn leads_north(&self, p: Point) -> bool {
+        match self.map[p] {
+            Tile::Start => self.connected(p).contains(&(p + N.vector())),
+            ref tile => tile.connections().contains(&N),
+        }
+    }
 
-        loop {
-            let tile = self.map[y][x].clone();
-            match tile {
-                Tile::NS => {
-                    dir = match dir {
-                        N => N,
-                        S => S,
-                        _ => panic!("Invalid direction: {:?}", dir),
-                    }
-                }
-                Tile::EW => {
-                    dir = match dir {
-                        E => E,
-                        W => W,
-                        _ => panic!("Invalid direction: {:?}", dir),
-                    }
-                }
-                Tile::NE => {
-                    dir = match dir {
-                        S => E,
-                        W => N,
-                        _ => panic!("Invalid direction: {:?}", dir),
-                    }
-                }
-                Tile::NW => {
-                    dir = match dir {
-                        S => W,
-                        E => N,
-                        _ => panic!("Invalid direction: {:?}", dir),
-                    }
-                }
-                Tile::SW => {
-                    dir = match dir {
-                        N => W,
-                        E => S,
-                        _ => panic!("Invalid direction: {:?}", dir),
-                    }
-                }
-                Tile::SE => {
-                    dir = match dir {
-                        N => E,
-                        W => S,
-                        _ => panic!("Invalid direction: {:?}", dir),
-                    }
-                }
-                Tile::Ground => {
-                    panic!("Invalid tile: {:?}", tile);
-                }
-                Tile::Start => {
-                    return _loop;
+    /// The tiles enclosed by the loop, found row by row: every crossing of the loop flips between outside and inside.
+    /// Only pipes leading north cross, so `L--7` flips once while `L--J` runs along the loop.
+    pub fn enclosed(&self) -> Option<Vec<Point>> {
+        let on_loop = self.loop_mask()?;
+        let mut enclosed = Vec::new();
+        for y in 0..self.map.height() {
+            let mut inside = false;
+            for x in 0..self.map.width() {
+                let p = Point::from((x, y));
+                if on_loop.contains(p) {
+                    inside ^= self.leads_north(p);
+                } else if inside {
+                    enclosed.push(p);
                 }
             }
-            _loop.push((x, y));
-            x = (x as i32 + dir.x()) as usize;
-            y = (y as i32 + dir.y()) as usize;
         }
+        Some(enclosed)
     }
 
-    fn shoelace(&self) -> i64 {
-        let points: Vec<(usize, usize)> = self.find_loop();
-        let len = points.len();
+    /// The loop as polygon vertices, every tile of the loop is one.
+    pub fn polygon(&self) -> Option<Vec<(i64, i64)>> {
+        Some(self.find_loop()?.into_iter().map(<(i64, i64)>::from).collect())
+    }
 
-        points.iter()
-            .enumerate()
-            .fold(0 as i64, |s, (i, (x, y))| {
-                let l = (i + 1) % len;
-                let _x: i64 = *x as i64;
-                let _y: i64 = *y as i64;
-                s + (_y * points[l].0 as i64) - (_x * points[l].1 as i64)
-            })
-            .abs()
-            .div(2)
+    /// The area enclosed by the centres of the loop tiles.
+    pub fn shoelace(&self) -> Option<i64> {
+        Some(geometry::area(&self.polygon()?))
     }
 }
 
@@ -193,34 +187,147 @@ impl Day10 {
             input
         }
     }
-    fn get_maze(&self) -> Maze {
-        let lines = read_lines(&self.input);
-        Maze::new(lines)
+    /// A random maze. The loop runs around a shape of cell columns, neighbouring columns overlap by at least
+    /// one cell so the loop never touches itself. Tiles off the loop are random pipes, except around the start.
+    pub fn generate(g: &mut Gen) -> String {
+        let (columns, height) = (g.usize(g.scaled()), g.usize(g.scaled()));
+        // The rows from top to bottom, exclusive, covered by each column
+        let mut spans: Vec<(usize, usize)> = Vec::new();
+        for _ in 0..columns {
+            let (above, below) = spans.last().copied().unwrap_or((0, height));
+            let top = g.usize(0..=below - 1);
+            spans.push((top, g.usize(top.max(above) + 1..=height)));
+        }
+        let filled = |x: usize, y: usize| x > 0 && y > 0 && spans.get(x - 1).is_some_and(|(top, bottom)| (top + 1..=*bottom).contains(&y));
+
+        // The corners of the cells are the tiles, the loop runs between filled and empty cells
+        let margin = g.usize(0..=2);
+        let mut tiles = vec![vec![None; columns + 1 + 2 * margin]; height + 1 + 2 * margin];
+        let mut on_loop = Vec::new();
+        for y in 0..=height {
+            for x in 0..=columns {
+                let (nw, ne, sw, se) = (filled(x, y), filled(x + 1, y), filled(x, y + 1), filled(x + 1, y + 1));
+                let tile = match (nw != ne, sw != se, ne != se, nw != sw) {
+                    (true, true, false, false) => Tile::NS,
+                    (false, false, true, true) => Tile::EW,
+                    (true, false, true, false) => Tile::NE,
+                    (true, false, false, true) => Tile::NW,
+                    (false, true, false, true) => Tile::SW,
+                    (false, true, true, false) => Tile::SE,
+                    _ => continue,
+                };
+                tiles[y + margin][x + margin] = Some(tile);
+                on_loop.push((x + margin, y + margin));
+            }
+        }
+        let (x, y) = *g.choose(&on_loop).expect("The loop has at least four tiles");
+        tiles[y][x] = Some(Tile::Start);
+        // Pipes next to the start must not look like a second way into the loop
+        for (nx, ny) in [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)] {
+            if let Some(tile @ None) = tiles.get_mut(ny).and_then(|row| row.get_mut(nx)) {
+                *tile = Some(Tile::Ground);
+            }
+        }
+
+        const JUNK: [Tile; 7] = [Tile::Ground, Tile::NS, Tile::EW, Tile::NE, Tile::NW, Tile::SW, Tile::SE];
+        tiles.into_iter()
+            .map(|row| row.into_iter()
+                .map(|tile| tile.unwrap_or_else(|| g.choose(&JUNK).unwrap().clone()).symbol())
+                .collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn get_maze(&self) -> Maze {
+        Maze::new(&self.input)
     }
+
+    /// Counts the enclosed tiles with a scanline instead of Pick's theorem.
+    pub fn part2_scanline(&self) -> Result<String, SolveError> {
+        let enclosed = self.get_maze().enclosed().ok_or_else(no_loop)?;
+        Ok(enclosed.len().to_string())
+    }
+}
+
+fn no_loop() -> SolveError {
+    SolveError::NoSolution("The start is not on a loop".to_string())
 }
 
 impl Day for Day10 {
     fn part1(&self) -> String {
-        let maze = self.get_maze();
-        let length = maze.find_loop().len();
-        let max_distance = (length as f32 / 2.0).ceil() as u32;
-        max_distance.to_string()
+        self.part1_with(&Context::default()).unwrap_or_else(|e| panic!("Invalid maze: {}", e))
     }
+
     fn part2(&self) -> String {
+        self.part2_with(&Context::default()).unwrap_or_else(|e| panic!("Invalid maze: {}", e))
+    }
+
+    fn part1_with(&self, _ctx: &Context) -> Result<String, SolveError> {
+        let maze = self.get_maze();
+        maze.find_loop().ok_or_else(no_loop)?;
+        let search = bfs(maze.start, |p| maze.connected(*p), |_| false);
+        let max_distance = search.distances.values().max().unwrap();
+        Ok(max_distance.to_string())
+    }
+
+    fn part2_with(&self, _ctx: &Context) -> Result<String, SolveError> {
+        // The enclosed tiles are the lattice points strictly inside the loop
+        let polygon = self.get_maze().polygon().ok_or_else(no_loop)?;
+        Ok(geometry::interior_points(&polygon).to_string())
+    }
+
+    fn alternatives(&self) -> Vec<Alternative<'_>> {
+        vec![
+            Alternative::new(Part::One, "half the loop", |_| {
+                let path = self.get_maze().find_loop().ok_or_else(no_loop)?;
+                Ok((path.len() / 2).to_string())
+            }),
+            Alternative::new(Part::Two, "scanline", |_| self.part2_scanline()),
+        ]
+    }
+
+    /// The loop drawn with box-drawing characters, enclosed tiles shaded and pipes not on the loop greyed out.
+    fn visualize(&self) -> Option<Canvas> {
         let maze = self.get_maze();
-        let points = maze.find_loop();
-        let area = maze.shoelace()
-            // Not sure why this, probably removing loop points itself
-            .mul(2)
-            .sub(points.len() as i64)
-            .div(2)
-            .add(1);
-        area.to_string()
+        let on_loop = maze.loop_mask()?;
+        let junk: Vec<Point> = maze.map.iter()
+            .map(|(p, _)| p)
+            .filter(|p| !on_loop.contains(*p))
+            .collect();
+        Some(Canvas::new(&maze.map, Tile::symbol)
+            .highlight(junk, Style::fg(Colour::Grey))
+            .highlight(maze.enclosed()?, Style::fg(Colour::Green).fill('░'))
+            .path(&maze.find_loop()?, true, Style::fg(Colour::Yellow))
+            .highlight([maze.start], Style::fg(Colour::Red).bold()))
+    }
+
+    /// Walks along the loop one tile per frame, ending with the full visualisation.
+    fn animate(&self, recorder: &mut Recorder) {
+        let maze = self.get_maze();
+        let Some(path) = maze.find_loop() else {
+            return;
+        };
+        let all: Vec<Point> = maze.map.iter().map(|(p, _)| p).collect();
+        let background = Canvas::new(&maze.map, Tile::symbol).highlight(all, Style::fg(Colour::Grey));
+        for walked in 1..=path.len() {
+            recorder.frame(|| background.clone()
+                .path(&path[..walked], false, Style::fg(Colour::Yellow))
+                .highlight([path[walked - 1]], Style::fg(Colour::Red).bold()));
+        }
+        if let Some(canvas) = self.visualize() {
+            recorder.keep(canvas);
+        }
+    }
+
+    fn validate(&self) -> Result<(), ParseError> {
+        Maze::parse(&self.input).map(|_| ())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::aoc::tools::prop;
+
     use super::*;
 
     const INPUT_1_1: &str = r#"
@@ -306,4 +413,56 @@ mod tests {
         let day10 = Day10::new(INPUT_2_3.to_string());
         assert_eq!(day10.part2(), "10".to_string());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_part2_scanline() {
+        for (input, expected) in [(INPUT_2_1, "4"), (INPUT_2_2, "8"), (INPUT_2_3, "10")] {
+            assert_eq!(Day10::new(input.to_string()).part2_scanline(), Ok(expected.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_visualize() {
+        let drawing = Day10::new(INPUT_1_1.to_string()).visualize().unwrap();
+        assert_eq!(drawing.render(false), ".....\n.┌─┐.\n.│░│.\n.└─┘.\n.....\n");
+    }
+
+    #[test]
+    fn test_animate() {
+        let mut recorder = Recorder::new();
+        Day10::new(INPUT_1_1.to_string()).animate(&mut recorder);
+        // One frame per tile of the loop and the final drawing
+        assert_eq!(recorder.frames().len(), 8 + 1);
+        assert_eq!(recorder.frames()[1].render(false).lines().nth(1), Some(".╷-7."));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(Day10::new(INPUT_1_2.to_string()).validate(), Ok(()));
+        let error = Day10::new("S-7\n|.|\nL-S".to_string()).validate().unwrap_err();
+        assert_eq!(error.to_string(), "line 3, column 3: Expected a single start 'S'");
+        let error = Day10::new("F-7\n|.|\nL-J".to_string()).validate().unwrap_err();
+        assert_eq!(error.to_string(), "line 3, column 4: Expected a start 'S', found none");
+        let error = Day10::new("S-7\n|x|".to_string()).validate().unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 2: Invalid character 'x'");
+    }
+
+    #[test]
+    fn test_no_loop() {
+        let day = Day10::new("...\n.S-".to_string());
+        let expected = Err(SolveError::NoSolution("The start is not on a loop".to_string()));
+        assert_eq!(day.part1_with(&Context::default()), expected);
+        assert_eq!(day.part2_with(&Context::default()), expected);
+        assert!(day.visualize().is_none());
+    }
+
+    #[test]
+    fn test_enclosed_tiles_agree() {
+        prop::assert_property("enclosed tiles", Day10::generate, |input| {
+            let day = Day10::new(input.clone());
+            prop::ensure_eq(day.part2(), day.part2_scanline().unwrap(), "enclosed tiles")?;
+            let path = day.get_maze().find_loop().unwrap();
+            prop::ensure_eq(day.part1(), (path.len() / 2).to_string(), "farthest distance")
+        });
+    }
+}
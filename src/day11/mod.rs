@@ -2,6 +2,11 @@ use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 use crate::aoc::Day;
+use crate::aoc::tools::{Grid, Point};
+use crate::aoc::tools::parse::{Field, ParseError};
+use crate::aoc::tools::prop::Gen;
+use crate::aoc::viz::record::Recorder;
+use crate::aoc::viz::{Canvas, Colour, Style};
 
 pub struct Day11 {
     input: String,
@@ -11,6 +16,17 @@ impl Day11 {
     pub fn new(input: String) -> Day11 {
         Day11 { input }
     }
+
+    /// A random image of galaxies, by default sparse enough to leave empty rows and columns.
+    /// The density of the generator overrides the random share of galaxies of up to 30%.
+    pub fn generate(g: &mut Gen) -> String {
+        let width = g.usize(g.scaled());
+        let density = g.density().unwrap_or_else(|| g.range(1..=30) as u64);
+        let rows = g.vec(g.scaled(), |g| {
+            (0..width).map(|_| if g.chance(density) { '#' } else { '.' }).collect::<String>()
+        });
+        rows.join("\n")
+    }
 }
 
 impl Day for Day11 {
@@ -35,82 +51,113 @@ impl Day for Day11 {
 
         total_distance.to_string()
     }
+
+    /// Galaxies in yellow, the empty rows and columns that expand shaded.
+    fn visualize(&self) -> Option<Canvas> {
+        let mut universe = Universe::new(&self.input);
+        universe.expand_by(2);
+        let expanding: Vec<Point> = universe.grid.iter()
+            .map(|(p, _)| p)
+            .filter(|p| universe.expanded_rows.contains(&p.y) || universe.expanded_columns.contains(&p.x))
+            .collect();
+        Some(Canvas::new(&universe.grid, |s| s.to_string().chars().next().unwrap())
+            .highlight(expanding, Style::fg(Colour::Blue).fill('░'))
+            .highlight(universe.galaxies(), Style::fg(Colour::Yellow).bold()))
+    }
+
+    /// Doubles the empty columns and then the empty rows, one per frame.
+    fn animate(&self, recorder: &mut Recorder) {
+        let mut universe = Universe::new(&self.input);
+        universe.expand_by(2);
+        let mut rows: Vec<Vec<char>> = universe.grid.rows()
+            .map(|row| row.iter().map(|s| s.to_string().chars().next().unwrap()).collect())
+            .collect();
+        let draw = |rows: &Vec<Vec<char>>| {
+            let grid = Grid::from_rows(rows.clone()).unwrap();
+            Canvas::new(&grid, |c| *c)
+                .highlight(grid.find_all(|c| *c == '░'), Style::fg(Colour::Blue))
+                .highlight(grid.find_all(|c| *c == '#'), Style::fg(Colour::Yellow).bold())
+        };
+
+        recorder.frame(|| draw(&rows));
+        // From the far side, so the indices of the remaining lines stay valid
+        for x in universe.expanded_columns.iter().rev() {
+            rows.iter_mut().for_each(|row| row.insert(*x as usize + 1, '░'));
+            recorder.frame(|| draw(&rows));
+        }
+        for y in universe.expanded_rows.iter().rev() {
+            rows.insert(*y as usize + 1, vec!['░'; rows[0].len()]);
+            recorder.frame(|| draw(&rows));
+        }
+    }
+
+    fn validate(&self) -> Result<(), ParseError> {
+        Universe::from_str(&self.input).map(|_| ())
+    }
 }
 
 #[derive(Clone, Debug, PartialOrd, PartialEq)]
-enum Space {
+pub enum Space {
     Empty,
     Galaxy,
 }
 
 impl Space {
-    fn from_char(c: char) -> Space {
+    pub fn from_char(c: char) -> Option<Space> {
         match c {
-            '.' => Space::Empty,
-            '#' => Space::Galaxy,
-            _ => panic!("Unknown space type"),
+            '.' => Some(Space::Empty),
+            '#' => Some(Space::Galaxy),
+            _ => None,
         }
     }
 }
 
-struct Universe {
-    grid: Vec<Vec<Space>>,
-    expanded_rows: Vec<usize>,
-    expanded_columns: Vec<usize>,
-    expansion: usize,
+impl Display for Space {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Space::Empty => write!(f, "."),
+            Space::Galaxy => write!(f, "#"),
+        }
+    }
+}
+
+pub struct Universe {
+    pub grid: Grid<Space>,
+    pub expanded_rows: Vec<i64>,
+    pub expanded_columns: Vec<i64>,
+    pub expansion: usize,
 }
 
 impl Universe {
-    fn new(input: &String) -> Universe {
-        Universe::from_str(input.as_str()).unwrap()
+    pub fn new(input: &str) -> Universe {
+        Universe::from_str(input).unwrap_or_else(|e| panic!("Invalid universe: {}", e))
     }
 
-    fn expand_by(&mut self, factor: usize) {
+    pub fn expand_by(&mut self, factor: usize) {
         self.expansion += factor;
-        let mut rows: Vec<usize> = self.grid.iter()
+        let mut rows: Vec<i64> = self.grid.rows()
             .enumerate()
             .filter(|(_, row)| row.iter()
                 .all(|space| space == &Space::Empty))
-            .map(|(i, _)| i)
+            .map(|(i, _)| i as i64)
             .collect();
 
-        let mut columns: Vec<usize> = self.get_columns().iter()
+        let mut columns: Vec<i64> = self.grid.columns()
             .enumerate()
-            .filter(|(_, column)| column.iter()
-                .all(|space| space == &Space::Empty))
-            .map(|(i, _)| i)
+            .filter_map(|(i, mut column)| column
+                .all(|space| space == &Space::Empty)
+                .then_some(i as i64))
             .collect();
 
         self.expanded_rows.append(&mut rows);
         self.expanded_columns.append(&mut columns);
     }
 
-    fn get_columns(&self) -> Vec<Vec<Space>> {
-        let mut columns = Vec::new();
-        for i in 0..self.grid[0].len() {
-            columns.push(self.get_column(i));
-        }
-        columns
-    }
-    fn get_column(&self, index: usize) -> Vec<Space> {
-        self.grid.iter()
-            .map(|row| row[index].clone())
-            .collect()
-    }
-
-    fn galaxies(&self) -> Vec<(usize, usize)> {
-        let mut galaxies = Vec::new();
-        for y in 0..self.grid.len() {
-            for x in 0..self.grid[y].len() {
-                if self.grid[y][x] == Space::Galaxy {
-                    galaxies.push((x, y));
-                }
-            }
-        }
-        galaxies
+    pub fn galaxies(&self) -> Vec<Point> {
+        self.grid.find_all(|space| space == &Space::Galaxy)
     }
 
-    fn galaxy_pairs(&self) -> Vec<((usize, usize), (usize, usize))> {
+    pub fn galaxy_pairs(&self) -> Vec<(Point, Point)> {
         let galaxies = self.galaxies();
         let mut pairs = Vec::new();
         for i in 0..galaxies.len() {
@@ -121,52 +168,40 @@ impl Universe {
         pairs
     }
 
-    fn distance(&self, a: (usize, usize), b: (usize, usize)) -> u64 {
-        let (x1, x2) = if a.0 > b.0 { (a.0, b.0) } else { (b.0, a.0) };
-        let (y1, y2) = if a.1 > b.1 { (a.1, b.1) } else { (b.1, a.1) };
-        let x = (x1 - x2) as u64;
-        let y = (y1 - y2) as u64;
+    pub fn distance(&self, a: Point, b: Point) -> u64 {
+        let (x1, x2) = (a.x.max(b.x), a.x.min(b.x));
+        let (y1, y2) = (a.y.max(b.y), a.y.min(b.y));
 
         let extra_rows = self.expanded_rows.iter()
-            .filter(|row| row < &&y1 && row > &&y2)
+            .filter(|row| (y2 + 1..y1).contains(row))
             .count() as u64;
 
         let extra_columns = self.expanded_columns.iter()
-            .filter(|row| row < &&x1 && row > &&x2)
+            .filter(|column| (x2 + 1..x1).contains(column))
             .count() as u64;
 
-        (x + y) + (extra_rows + extra_columns) * (self.expansion as u64 - 1)
+        a.manhattan(b) + (extra_rows + extra_columns) * (self.expansion as u64 - 1)
     }
 }
 
 impl Display for Universe {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.grid.iter()
-            .map(|row| row.iter()
-                .map(|space| match space {
-                    Space::Empty => '.',
-                    Space::Galaxy => '#',
-                })
-                .collect::<String>())
-            .collect::<Vec<String>>()
-            .join("\n"))
+        write!(f, "{}", self.grid)
     }
 }
 
 impl FromStr for Universe {
-    type Err = ();
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let grid = s
-            .trim()
-            .lines()
-            .map(|line| line.trim().chars().map(Space::from_char).collect())
-            .collect();
+        let grid = Field::new(s).grid(Space::from_char)?;
         Ok(Universe { grid, expanded_rows: vec![], expanded_columns: vec![], expansion: 0 })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::aoc::tools::prop::{assert_property, ensure_eq, Gen};
+
     use super::*;
 
     const INPUT: &str = r#"
@@ -188,9 +223,27 @@ mod tests {
         assert_eq!(day11.part1(), "374".to_string());
     }
 
+    #[test]
+    fn test_validate() {
+        assert_eq!(Day11::new(INPUT.to_string()).validate(), Ok(()));
+        let error = Day11::new("..#\n.S.".to_string()).validate().unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 2: Invalid character 'S'");
+    }
+
+    #[test]
+    fn test_animate() {
+        let mut recorder = Recorder::new();
+        Day11::new(INPUT.to_string()).animate(&mut recorder);
+        // The original, then three columns and two rows
+        assert_eq!(recorder.frames().len(), 6);
+        let expanded = recorder.frames().last().unwrap().render(false).replace('░', ".");
+        assert_eq!(expanded.lines().next(), Some("....#........"));
+        assert_eq!(expanded.lines().count(), 12);
+    }
+
     #[test]
     fn test_part2_1() {
-        let mut universe = Universe::new(&INPUT.to_string());
+        let mut universe = Universe::new(INPUT);
         universe.expand_by(10);
 
         let total_distance: u64 = universe.galaxy_pairs().iter()
@@ -202,7 +255,7 @@ mod tests {
 
     #[test]
     fn test_part2_2() {
-        let mut universe = Universe::new(&INPUT.to_string());
+        let mut universe = Universe::new(INPUT);
         universe.expand_by(100);
 
         let total_distance: u64 = universe.galaxy_pairs().iter()
@@ -211,4 +264,34 @@ mod tests {
 
         assert_eq!(total_distance.to_string(), "8410".to_string());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_generate_density() {
+        let galaxies = |density| Day11::generate(&mut Gen::new(3, 40).with_density(density)).matches('#').count();
+        assert_eq!(galaxies(0), 0);
+        assert!((1..200).contains(&galaxies(2)));
+        assert_eq!(Day11::generate(&mut Gen::new(3, 40).with_density(100)).matches('.').count(), 0);
+    }
+
+    #[test]
+    fn test_distance_is_a_metric() {
+        let universe = |g: &mut Gen| (Day11::generate(g), g.usize(1..=10));
+        assert_property("distances", universe, |(input, factor)| {
+            let mut universe = Universe::new(input);
+            universe.expand_by(*factor);
+            let galaxies = universe.galaxies();
+            for a in &galaxies {
+                ensure_eq(universe.distance(*a, *a), 0, "distance to itself")?;
+                for b in &galaxies {
+                    ensure_eq(universe.distance(*a, *b), universe.distance(*b, *a), &format!("distances between {} and {}", a, b))?;
+                    for c in &galaxies {
+                        if universe.distance(*a, *c) > universe.distance(*a, *b) + universe.distance(*b, *c) {
+                            return Err(format!("{} to {} is longer than the way via {}", a, c, b));
+                        }
+                    }
+                }
+            }
+            Ok(())
+        });
+    }
+}
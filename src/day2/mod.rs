@@ -1,45 +1,55 @@
 use std::cmp::max;
 
-use crate::aoc::Day;
-use crate::aoc::tools::read_lines;
+use crate::aoc::{Context, Day};
+use crate::aoc::runner::SolveError;
+use crate::aoc::tools::checked::{checked_product, checked_sum, Overflow};
+use crate::aoc::tools::parse::{Field, parse_lines, ParseError, Pattern};
+use crate::aoc::tools::prop::Gen;
 
 pub struct Day2 {
     input: String,
 }
 
-struct Cubes {
-    red: u32,
-    green: u32,
-    blue: u32,
+#[derive(Debug)]
+pub struct Cubes {
+    pub red: u32,
+    pub green: u32,
+    pub blue: u32,
 }
 
 impl Cubes {
-    fn new(r: u32, g: u32, b: u32) -> Cubes {
+    pub fn new(r: u32, g: u32, b: u32) -> Cubes {
         Cubes { red: r, green: g, blue: b }
     }
 
-    fn total(&self) -> u32 {
+    pub fn total(&self) -> u32 {
         self.red + self.green + self.blue
     }
 
-    fn possible(&self, bag: &Cubes) -> bool {
+    pub fn possible(&self, bag: &Cubes) -> bool {
         (self.red == 0 || self.red <= bag.red)
             && (self.green == 0 || self.green <= bag.green)
             && (self.blue == 0 || self.blue <= bag.blue)
     }
 
-    fn power(&self) -> u32 {
+    pub fn power(&self) -> u32 {
         self.red * self.green * self.blue
     }
+
+    /// Like `power`, but reports overflow instead of panicking or wrapping.
+    pub fn checked_power(&self) -> Result<u32, Overflow> {
+        checked_product([self.red, self.green, self.blue])
+    }
 }
 
-struct Game {
-    id: u32,
-    moves: Vec<Cubes>,
+#[derive(Debug)]
+pub struct Game {
+    pub id: u32,
+    pub moves: Vec<Cubes>,
 }
 
 impl Game {
-    fn min_bag(&self) -> Cubes {
+    pub fn min_bag(&self) -> Cubes {
         self.moves.iter()
             .fold(Cubes::new(0, 0, 0), |acc, c| {
                 Cubes::new(
@@ -52,7 +62,7 @@ impl Game {
 }
 
 impl Game {
-    fn new(id: u32, moves: Vec<Cubes>) -> Game {
+    pub fn new(id: u32, moves: Vec<Cubes>) -> Game {
         Game { id, moves }
     }
 }
@@ -62,48 +72,62 @@ impl Day2 {
         Day2 { input }
     }
 
-    fn parse_game(l: &String) -> Game {
-        let mut parts = l.split(":");
-
-        let id = parts.next()
-            .unwrap()
-            .trim()
-            .strip_prefix("Game")
-            .unwrap()
-            .trim()
-            .parse::<u32>()
-            .unwrap();
-
-        let moves = parts.next()
-            .unwrap().split(";")
-            .map(|p| Day2::parse_move(p))
-            .collect();
-
-        Game::new(id, moves)
-    }
-
-    fn parse_move(s: &str) -> Cubes {
-        let parts = s.trim().split(",");
-        let mut r = 0;
-        let mut g = 0;
-        let mut b = 0;
-
-        parts.for_each(|p| {
-            let mut color = p.trim().split(" ");
-            let count = color.next().unwrap().parse::<u32>().unwrap();
-            let c = color.next().unwrap();
-            match c {
-                "red" => r = count,
-                "green" => g = count,
-                "blue" => b = count,
-                _ => panic!("Invalid color {}", c),
+    pub fn parse_game(line: Field) -> Result<Game, ParseError> {
+        let game = line.matches(&Pattern::new("Game {id}: {moves}"))?;
+        let moves = game.get("moves").split(";").into_iter()
+            .map(Day2::parse_move)
+            .collect::<Result<Vec<Cubes>, ParseError>>()?;
+
+        Ok(Game::new(game.parse("id")?, moves))
+    }
+
+    pub fn parse_move(s: Field) -> Result<Cubes, ParseError> {
+        let pattern = Pattern::new("{count} {color}");
+        let mut cubes = Cubes::new(0, 0, 0);
+
+        for part in s.split(",") {
+            let draw = part.matches(&pattern)?;
+            let count = draw.parse::<u32>("count")?;
+            let color = draw.get("color");
+            match color.text {
+                "red" => cubes.red = count,
+                "green" => cubes.green = count,
+                "blue" => cubes.blue = count,
+                _ => return Err(color.error(format!("Invalid color {}", color.text))),
             }
+        }
+
+        Ok(cubes)
+    }
+
+    /// Random games numbered from 1, every draw names each colour at most once.
+    pub fn generate(g: &mut Gen) -> String {
+        let games = g.vec(g.scaled(), |g| {
+            g.vec(1..=4, |g| {
+                let mut colours = vec!["red", "green", "blue"];
+                let count = g.usize(1..=3);
+                (0..count)
+                    .map(|_| {
+                        let colour = colours.remove(g.usize(0..=colours.len() - 1));
+                        format!("{} {}", g.range(1..=20), colour)
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            }).join("; ")
         });
+        games.iter()
+            .enumerate()
+            .map(|(i, moves)| format!("Game {}: {}", i + 1, moves))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
 
-        Cubes::new(r, g, b)
+    pub fn get_games(&self) -> Vec<Game> {
+        parse_lines(&self.input, Day2::parse_game)
+            .unwrap_or_else(|e| panic!("Invalid game: {}", e))
     }
 
-    fn possible(bag: &Cubes, game: &Game) -> bool {
+    pub fn possible(bag: &Cubes, game: &Game) -> bool {
         game.moves.iter()
             .fold(true,
                   |acc, m|
@@ -115,33 +139,42 @@ impl Day2 {
 
 impl Day for Day2 {
     fn part1(&self) -> String {
+        self.part1_with(&Context::default()).unwrap_or_else(|e| panic!("Sum of game ids does not fit: {}", e))
+    }
+
+    fn part2(&self) -> String {
+        self.part2_with(&Context::default()).unwrap_or_else(|e| panic!("Sum of powers does not fit: {}", e))
+    }
+
+    fn part1_with(&self, _ctx: &Context) -> Result<String, SolveError> {
         let cubes = Cubes::new(12, 13, 14);
 
-        let valid_games = read_lines(&self.input).iter()
-            .map(|l| Day2::parse_game(l))
-            .filter(|g| Day2::possible(&cubes, &g))
+        let valid_games = self.get_games().iter()
+            .filter(|g| Day2::possible(&cubes, g))
             .map(|g| g.id)
             .collect::<Vec<u32>>();
 
-        valid_games.iter()
-            .fold(0, |acc, id| acc + id)
-            .to_string()
+        Ok(checked_sum(valid_games)?.to_string())
     }
 
-    fn part2(&self) -> String {
-        let powers = read_lines(&self.input).iter()
-            .map(|l| Day2::parse_game(l))
-            .map(|g| g.min_bag())
-            .map(|g| g.power())
-            .fold(0, |acc, p| acc + p);
+    fn part2_with(&self, _ctx: &Context) -> Result<String, SolveError> {
+        let powers = self.get_games().iter()
+            .map(|g| g.min_bag().checked_power())
+            .collect::<Result<Vec<u32>, Overflow>>()
+            .and_then(checked_sum)?;
+
+        Ok(powers.to_string())
+    }
 
-        powers.to_string()
+    fn validate(&self) -> Result<(), ParseError> {
+        parse_lines(&self.input, Day2::parse_game).map(|_| ())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::aoc::Day;
+    use crate::aoc::tools::prop::assert_property;
 
     const INPUT: &str = r#"Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
         Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
@@ -162,4 +195,23 @@ mod tests {
     fn test_part2() {
         assert_eq!(day().part2(), "2286");
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[should_panic(expected = "Sum of powers does not fit")]
+    fn test_part2_overflow() {
+        let day = super::Day2::new("Game 1: 5000 red, 5000 green, 5000 blue".to_string());
+        day.part2();
+    }
+
+    #[test]
+    fn test_min_bag_is_possible() {
+        assert_property("every game fits its minimal bag", super::Day2::generate, |input| {
+            for game in super::Day2::new(input.clone()).get_games() {
+                if !super::Day2::possible(&game.min_bag(), &game) {
+                    return Err(format!("Game {} does not fit {:?}", game.id, game.min_bag()));
+                }
+            }
+            Ok(())
+        });
+    }
+}
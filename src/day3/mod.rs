@@ -1,7 +1,10 @@
 use regex::Regex;
 
 use crate::aoc::Day;
-use crate::aoc::tools::read_lines;
+use crate::aoc::tools::{Grid, Point};
+use crate::aoc::tools::parse::{Cursor, Field, ParseError};
+use crate::aoc::tools::prop::Gen;
+use crate::aoc::viz::{Canvas, Colour, Style};
 
 pub struct Day3 {
     input: String,
@@ -12,35 +15,68 @@ impl Day3 {
         Day3 { input }
     }
 
-    fn get_grid(&self) -> Grid {
-        Grid::new(read_lines(&self.input).iter()
-            .map(|l| l.chars().collect::<Vec<char>>())
-            .collect::<Vec<Vec<char>>>())
+    /// A random rectangular schematic of numbers, symbols and empty space.
+    pub fn generate(g: &mut Gen) -> String {
+        let width = g.usize(g.scaled());
+        let rows = g.vec(g.scaled(), |g| {
+            let mut row = String::new();
+            while row.len() < width {
+                // Numbers have at most three digits, so two of them are never adjacent
+                let number_ends_row = row.ends_with(|c: char| c.is_ascii_digit());
+                match g.below(4) {
+                    0 | 1 => row.push('.'),
+                    2 if !number_ends_row => row.push_str(&g.range(1..=999).to_string()),
+                    2 => row.push('.'),
+                    _ => row.push(*g.choose(&['*', '#', '+', '$', '/', '@', '%', '=', '&', '-']).unwrap()),
+                }
+            }
+            row.truncate(width);
+            row
+        });
+        rows.join("\n")
+    }
+
+    /// A rectangular schematic whose numbers fit an `u32`.
+    pub fn parse_schematic(input: &str) -> Result<Schematic, ParseError> {
+        let field = Field::new(input);
+        let grid = field.grid(Some)?;
+        let numbers = Regex::new("[0-9]+").unwrap();
+        for line in field.lines() {
+            let mut cursor = Cursor::new(line);
+            for number in numbers.find_iter(line.text) {
+                cursor.slice(number.range()).parse::<u32>()?;
+            }
+        }
+        Ok(Schematic::new(grid))
+    }
+
+    pub fn get_schematic(&self) -> Schematic {
+        Day3::parse_schematic(&self.input).unwrap_or_else(|e| panic!("Invalid schematic: {}", e))
     }
 }
 
-struct Grid {
+pub struct Schematic {
     symbol_regex: Regex,
-    grid: Vec<Vec<char>>,
+    grid: Grid<char>,
 }
 
-impl Grid {
-    fn new(grid: Vec<Vec<char>>) -> Grid {
-        Grid { symbol_regex: Regex::new("[^\\d.]").unwrap(), grid }
+impl Schematic {
+    pub fn new(grid: Grid<char>) -> Schematic {
+        Schematic { symbol_regex: Regex::new("[^\\d.]").unwrap(), grid }
     }
 
-    fn get(&self, x: usize, y: usize) -> Option<char> {
-        self.grid.get(y).and_then(|l| l.get(x)).copied()
+    pub fn get(&self, x: usize, y: usize) -> Option<char> {
+        self.grid.get(Point::from((x, y))).copied()
     }
 
-    fn is_digit(&self, x: usize, y: usize) -> bool {
+    pub fn is_digit(&self, x: usize, y: usize) -> bool {
         match self.get(x, y) {
-            Some(c) => c.is_digit(10),
+            Some(c) => c.is_ascii_digit(),
             None => false,
         }
     }
 
-    fn get_adjacent_numbers(&self, x: usize, y: usize) -> Vec<i64> {
+    pub fn get_adjacent_numbers(&self, x: usize, y: usize) -> Vec<i64> {
         let mut adjacent_numbers = Vec::new();
         for j in y.saturating_sub(1)..y + 2 {
             let mut is_num = false;
@@ -50,7 +86,7 @@ impl Grid {
                     continue;
                 }
                 let symbol = self.get(i, j);
-                if symbol.is_some() && symbol.unwrap().is_digit(10) {
+                if symbol.is_some_and(|c| c.is_ascii_digit()) {
                     // Only add number if it is not part a previously added number
                     if !is_num {
                         let num = self.get_full_number(i, j);
@@ -64,7 +100,7 @@ impl Grid {
         }
         adjacent_numbers
     }
-    fn gear_ratio(&self, x: usize, y: usize) -> Option<i64> {
+    pub fn gear_ratio(&self, x: usize, y: usize) -> Option<i64> {
         let gear = match self.get(x, y) {
             Some(c) => c == '*',
             None => false,
@@ -78,10 +114,10 @@ impl Grid {
         if adj.len() != 2 {
             return None;
         }
-        return Some(adj.iter().fold(1, |acc, x| acc * x));
+        Some(adj.iter().product())
     }
 
-    fn get_full_number(&self, x: usize, y: usize) -> i64 {
+    pub fn get_full_number(&self, x: usize, y: usize) -> i64 {
         if !self.is_digit(x, y) {
             return 0;
         }
@@ -105,48 +141,62 @@ impl Grid {
             end = next
         }
 
-        self.grid[y][start..end + 1]
+        self.grid.row(y)[start..end + 1]
             .iter().collect::<String>()
             .parse::<i64>().unwrap()
     }
 
-    fn has_adjacent_symbol(&self, x: usize, y: usize) -> bool {
-        for i in x.saturating_sub(1)..x + 2 {
-            for j in y.saturating_sub(1)..y + 2 {
-                if i == x && j == y {
-                    continue;
-                }
-                let symbol = self.get(i, j);
-                if symbol.is_some() && self.symbol_regex.is_match(&symbol.unwrap().to_string()) {
-                    return true;
+    pub fn has_adjacent_symbol(&self, x: usize, y: usize) -> bool {
+        self.grid.neighbours8(Point::from((x, y)))
+            .any(|p| self.symbol_regex.is_match(&self.grid[p].to_string()))
+    }
+
+
+    /// The digits of all numbers adjacent to a symbol.
+    pub fn part_number_digits(&self) -> Vec<Point> {
+        let mut digits = Vec::new();
+        for y in 0..self.height() {
+            let mut number = Vec::new();
+            let mut is_part = false;
+            // One step past the end closes a number at the edge
+            for x in 0..=self.width() {
+                if x < self.width() && self.is_digit(x, y) {
+                    number.push(Point::from((x, y)));
+                    is_part |= self.has_adjacent_symbol(x, y);
+                } else {
+                    if is_part {
+                        digits.append(&mut number);
+                    }
+                    number.clear();
+                    is_part = false;
                 }
             }
         }
-        false
+        digits
     }
 
-
-    fn width(&self) -> usize {
-        self.grid[0].len()
+    pub fn width(&self) -> usize {
+        self.grid.width()
     }
 
-    fn height(&self) -> usize {
-        self.grid.len()
+    pub fn height(&self) -> usize {
+        self.grid.height()
     }
 }
 
 impl Day for Day3 {
     fn part1(&self) -> String {
-        let grid = self.get_grid();
+        let grid = self.get_schematic();
 
         let mut sum = 0;
 
         let mut current: Option<String> = None;
         let mut adjacent = false;
 
-        for y in 0..grid.width() {
-            for x in 0..grid.height() {
-                if grid.is_digit(x, y) {
+        for y in 0..grid.height() {
+            // One step past the end closes a number at the edge
+            for x in 0..=grid.width() {
+                if x < grid.width() && grid.is_digit(x, y) {
                     match current {
                         Some(ref mut s) => s.push(grid.get(x, y).unwrap()),
                         None => current = Some(grid.get(x, y).unwrap().to_string()),
@@ -155,8 +205,8 @@ impl Day for Day3 {
                         adjacent = true;
                     }
                 } else {
-                    if adjacent && current.is_some() {
-                        sum += current.unwrap().parse::<u32>().unwrap()
+                    if let Some(number) = current.filter(|_| adjacent) {
+                        sum += number.parse::<u32>().unwrap()
                     }
 
                     current = None;
@@ -169,22 +219,43 @@ impl Day for Day3 {
     }
 
     fn part2(&self) -> String {
-        let grid = self.get_grid();
+        let grid = self.get_schematic();
 
         let mut sum = 0;
         for y in 0..grid.height() {
             for x in 0..grid.width() {
-                sum += grid.gear_ratio(x, y).unwrap_or_else(|| 0)
+                sum += grid.gear_ratio(x, y).unwrap_or(0)
             }
         }
 
         sum.to_string()
     }
+
+    /// Part numbers in green, other numbers in red, symbols in yellow and gears in magenta.
+    fn visualize(&self) -> Option<Canvas> {
+        let schematic = self.get_schematic();
+        let grid = &schematic.grid;
+        let gears: Vec<Point> = grid.iter()
+            .map(|(p, _)| p)
+            .filter(|p| schematic.gear_ratio(p.x as usize, p.y as usize).is_some())
+            .collect();
+        Some(Canvas::new(grid, |c| *c)
+            .highlight(grid.find_all(|c| *c == '.'), Style::fg(Colour::Grey))
+            .highlight(grid.find_all(|c| c.is_ascii_digit()), Style::fg(Colour::Red))
+            .highlight(schematic.part_number_digits(), Style::fg(Colour::Green))
+            .highlight(grid.find_all(|c| !c.is_ascii_digit() && *c != '.'), Style::fg(Colour::Yellow).bold())
+            .highlight(gears, Style::fg(Colour::Magenta)))
+    }
+
+    fn validate(&self) -> Result<(), ParseError> {
+        Day3::parse_schematic(&self.input).map(|_| ())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::aoc::Day;
+    use crate::aoc::tools::prop::{assert_property, ensure_eq};
 
     const INPUT: &str = r#"
     467..114..
@@ -207,8 +278,57 @@ mod tests {
         assert_eq!(day().part1(), "4361");
     }
 
+    #[test]
+    fn test_part1_wider_than_high() {
+        assert_eq!(super::Day3::new("...*5\n.....".to_string()).part1(), "5");
+    }
+
     #[test]
     fn test_part2() {
         assert_eq!(day().part2(), "467835");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_part_number_digits() {
+        let digits = day().get_schematic().part_number_digits();
+        // All numbers except 114 and 58
+        assert_eq!(digits.len(), 7 * 3 + 2);
+        assert!(!digits.contains(&super::Point::new(7, 5)));
+    }
+
+    #[test]
+    fn test_part1_number_at_row_end() {
+        // The numbers must not run on into the next row
+        assert_eq!(super::Day3::new("1\n1\n*".to_string()).part1(), "1");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(day().validate(), Ok(()));
+        let error = super::Day3::new("..*.........\n.99999999999".to_string()).validate().unwrap_err();
+        assert_eq!((error.line, error.column), (2, 2));
+        let error = super::Day3::new("...\n..".to_string()).validate().unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 1: Expected 3 cells, found 2");
+    }
+
+    #[test]
+    fn test_part1_matches_part_number_digits() {
+        assert_property("part numbers", super::Day3::generate, |input| {
+            let day = super::Day3::new(input.clone());
+            let schematic = day.get_schematic();
+            let mut digits = schematic.part_number_digits();
+            // Consecutive digits in a row form one number
+            digits.push(super::Point::new(-1, -1));
+            let mut sum = 0;
+            let mut number = 0;
+            for (i, p) in digits.iter().enumerate().take(digits.len() - 1) {
+                number = number * 10 + schematic.get(p.x as usize, p.y as usize).unwrap().to_digit(10).unwrap();
+                if digits[i + 1] != super::Point::new(p.x + 1, p.y) {
+                    sum += number;
+                    number = 0;
+                }
+            }
+            ensure_eq(day.part1(), sum.to_string(), "sums of part numbers")
+        });
+    }
+}
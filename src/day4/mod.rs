@@ -1,13 +1,24 @@
+use std::collections::HashSet;
+use std::iter;
 use std::str::FromStr;
-use std::string::ParseError;
 
-use crate::aoc::Day;
-use crate::aoc::tools::read_lines;
+use log::debug;
 
-struct Card {
-    id: usize,
-    winning_numbers: Vec<usize>,
-    numbers: Vec<usize>,
+use crate::aoc::{Context, Day};
+use crate::aoc::runner::SolveError;
+use crate::aoc::tools::bitset::FixedBitSet;
+use crate::aoc::tools::checked::{checked_product, checked_sum, Checked, Overflow};
+use crate::aoc::tools::memo::Memo;
+use crate::aoc::tools::parse::{Field, parse_lines, ParseError, Pattern};
+use crate::aoc::tools::prop::Gen;
+
+/// Numbers below 128, enough for the two digit numbers on puzzle cards.
+type SmallSet = FixedBitSet<2>;
+
+pub struct Card {
+    pub id: usize,
+    pub winning_numbers: Vec<usize>,
+    pub numbers: Vec<usize>,
 }
 
 impl Clone for Card {
@@ -17,70 +28,76 @@ impl Clone for Card {
 }
 
 impl Card {
-    fn new(id: usize, winning_numbers: Vec<usize>, numbers: Vec<usize>) -> Card {
+    pub fn new(id: usize, winning_numbers: Vec<usize>, numbers: Vec<usize>) -> Card {
         Card { id, winning_numbers, numbers }
     }
 
-    fn get_winners(&self) -> Vec<usize> {
+    /// Puzzle numbers fit a bitset, a set of larger numbers would allocate a bit for every number below them.
+    pub fn get_winners(&self) -> Vec<usize> {
+        if self.winning_numbers.iter().all(|n| *n < SmallSet::CAPACITY) {
+            let winning: SmallSet = self.winning_numbers.iter().copied().collect();
+            self.numbers_where(|n| winning.contains(n))
+        } else {
+            let winning: HashSet<usize> = self.winning_numbers.iter().copied().collect();
+            self.numbers_where(|n| winning.contains(&n))
+        }
+    }
+
+    fn numbers_where(&self, predicate: impl Fn(usize) -> bool) -> Vec<usize> {
         self.numbers.iter()
-            .filter(|n| self.winning_numbers.contains(n))
-            .map(|n| *n)
+            .filter(|n| predicate(**n))
+            .copied()
             .collect::<Vec<usize>>()
     }
 
-    fn read_numbers(s: &str) -> Vec<usize> {
-        s.split_whitespace()
-            .map(|n| n.parse::<usize>().unwrap())
-            .collect::<Vec<usize>>()
+    pub fn parse(line: Field) -> Result<Card, ParseError> {
+        let card = line.matches(&Pattern::new("Card {id}: {winning} | {numbers}"))?;
+        Ok(Card::new(card.parse("id")?, card.list("winning")?, card.list("numbers")?))
     }
 }
 
-struct Table {
-    cards: Vec<Card>,
+pub struct Table {
+    pub cards: Vec<Card>,
 }
 
 impl Table {
-    fn new(cards: Vec<Card>) -> Table {
+    pub fn new(cards: Vec<Card>) -> Table {
         Table { cards }
     }
 
-    fn get_value(&self) -> usize {
+    /// Numbers may repeat on a card, so a card can have more than 64 winners and a value that does not fit.
+    pub fn get_value(&self) -> Result<usize, Overflow> {
         self.cards.iter()
             // Get number of winning numbers for each card
             .map(|c| c.get_winners().len())
             // Remove cards with no winning numbers
             .filter(|s| *s > 0)
             // Value is 2 ^ (w-1) for each card
-            .map(|w| 1 << (w - 1))
-            .sum()
-    }
-
-    fn play(&mut self) -> usize {
-        let mut total_cards = 0;
-
-        let mut hand = Vec::new();
-        hand.append(&mut self.cards.clone());
-
-        while hand.len() > 0 {
-            let card = hand.pop().unwrap();
-            total_cards += 1;
-
-            let winners = card.get_winners().len();
-            if winners == 0 {
-                continue;
-            }
-
-            for i in card.id + 1..card.id + winners + 1 {
-                match self.get_copy(i) {
-                    Some(copy) => hand.push(copy),
-                    None => (),
-                }
-            }
-        }
-
-        total_cards
-    }
-    fn get_copy(&self, id: usize) -> Option<Card> {
+            .map(|w| checked_product(iter::repeat_n(2, w - 1)))
+            .collect::<Result<Vec<usize>, Overflow>>()
+            .and_then(checked_sum)
+    }
+
+    /// Total number of cards after all copies are won.
+    /// Each card wins the same copies every time, so the count per card is computed once.
+    /// A card only wins copies of later cards, so filling the cache from the last card keeps the recursion flat.
+    /// The count of a card may double with every card after it, so it may not fit either.
+    pub fn play(&self) -> Result<usize, Overflow> {
+        let mut won = Memo::new(|memo, id: &usize| {
+            let winners = self.cards[id - 1].get_winners().len();
+            let last = (id + winners).min(self.cards.len());
+            (id + 1..=last)
+                .map(|i| memo.get(i))
+                .collect::<Result<Vec<usize>, Overflow>>()
+                .and_then(|copies| checked_sum(copies)?.try_add(1))
+        });
+
+        let counts = (1..=self.cards.len()).rev().map(|id| won.get(id)).collect::<Result<Vec<usize>, Overflow>>();
+        debug!("Card cache: {}", won.stats());
+        checked_sum(counts?.into_iter().rev())
+    }
+
+    pub fn get_copy(&self, id: usize) -> Option<Card> {
         if id > self.cards.len() {
             return None;
         }
@@ -92,15 +109,7 @@ impl FromStr for Card {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts = s.split(":").collect::<Vec<&str>>();
-        let id = parts[0]
-            .strip_prefix("Card").unwrap().trim()
-            .parse::<usize>().unwrap();
-
-        let number_parts = parts[1].split("|").collect::<Vec<&str>>();
-        let winning_numbers = Card::read_numbers(number_parts[0]);
-        let numbers = Card::read_numbers(number_parts[1]);
-        Ok(Card::new(id, winning_numbers, numbers))
+        Card::parse(Field::new(s))
     }
 }
 
@@ -113,11 +122,32 @@ impl Day4 {
         Day4 { input }
     }
 
-    fn get_table(&self) -> Table {
-        let cards = read_lines(&self.input)
-            .iter()
-            .map(|l| l.parse::<Card>().unwrap())
-            .collect::<Vec<Card>>();
+    /// Random cards numbered from 1, the numbers on each side of a card are distinct.
+    pub fn generate(g: &mut Gen) -> String {
+        let side = |g: &mut Gen, len: usize| {
+            let mut numbers: Vec<u64> = Vec::new();
+            while numbers.len() < len {
+                let n = g.below(99) + 1;
+                if !numbers.contains(&n) {
+                    numbers.push(n);
+                }
+            }
+            numbers.iter().map(|n| format!("{:2}", n)).collect::<Vec<String>>().join(" ")
+        };
+        let cards = g.vec(g.scaled(), |g| {
+            let (winning, numbers) = (g.usize(1..=5), g.usize(1..=8));
+            format!("{} | {}", side(g, winning), side(g, numbers))
+        });
+        cards.iter()
+            .enumerate()
+            .map(|(i, card)| format!("Card {:3}: {}", i + 1, card))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn get_table(&self) -> Table {
+        let cards = parse_lines(&self.input, Card::parse)
+            .unwrap_or_else(|e| panic!("Invalid card: {}", e));
 
         Table::new(cards)
     }
@@ -125,18 +155,30 @@ impl Day4 {
 
 impl Day for Day4 {
     fn part1(&self) -> String {
-        self.get_table().get_value().to_string()
+        self.part1_with(&Context::default()).unwrap_or_else(|e| panic!("Card values do not fit: {}", e))
     }
 
     fn part2(&self) -> String {
-        // Get the table
-        self.get_table().play().to_string()
+        self.part2_with(&Context::default()).unwrap_or_else(|e| panic!("Card counts do not fit: {}", e))
+    }
+
+    fn part1_with(&self, _ctx: &Context) -> Result<String, SolveError> {
+        Ok(self.get_table().get_value()?.to_string())
+    }
+
+    fn part2_with(&self, _ctx: &Context) -> Result<String, SolveError> {
+        Ok(self.get_table().play()?.to_string())
+    }
+
+    fn validate(&self) -> Result<(), ParseError> {
+        parse_lines(&self.input, Card::parse).map(|_| ())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::aoc::Day;
+    use crate::aoc::{Context, Day};
+    use crate::aoc::tools::prop::{assert_property, ensure_eq};
 
     const INPUT: &str = r#"
         Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
@@ -160,4 +202,44 @@ mod tests {
     fn test_part2() {
         assert_eq!(day().part2(), "30");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_long_chain() {
+        // Every card wins a copy of the next one
+        let n: usize = 100_000;
+        let input = (1..=n).map(|id| format!("Card {}: 1 | 1", id)).collect::<Vec<_>>().join("\n");
+        assert_eq!(super::Day4::new(input).part2(), (n * (n + 1) / 2).to_string());
+    }
+
+    #[test]
+    fn test_repeated_winners() {
+        let day = super::Day4::new(format!("Card 1: 1 | {}", "1 ".repeat(70)));
+        let error = day.part1_with(&Context::default()).unwrap_err();
+        assert_eq!(error.to_string(), "9223372036854775808 * 2 overflows usize");
+        assert_eq!(day.part2(), "1");
+    }
+
+    #[test]
+    fn test_large_numbers() {
+        let day = super::Day4::new("Card 1: 41 48 99999999999999 | 83 86 6".to_string());
+        assert_eq!(day.part1(), "0");
+        let day = super::Day4::new("Card 1: 41 48 99999999999999 | 48 99999999999999 6".to_string());
+        assert_eq!(day.part1(), "2");
+    }
+
+    #[test]
+    fn test_play_matches_copying() {
+        // Hand out the copies card by card instead of counting them per card
+        assert_property("cards won", super::Day4::generate, |input| {
+            let table = super::Day4::new(input.clone()).get_table();
+            let mut copies = vec![1; table.cards.len()];
+            for (i, card) in table.cards.iter().enumerate() {
+                let won = card.get_winners().len();
+                for j in i + 1..(i + 1 + won).min(copies.len()) {
+                    copies[j] += copies[i];
+                }
+            }
+            ensure_eq(table.play().unwrap(), copies.iter().sum(), "total cards")
+        });
+    }
+}
@@ -1,12 +1,25 @@
 use std::{fmt, thread};
+use std::cmp::min;
 use std::fmt::{Display, Formatter};
 use std::hash::Hash;
 use std::ops::Range;
 use std::str::FromStr;
-use std::string::ParseError;
 
-use crate::aoc::Day;
-use crate::aoc::tools::read_blocks;
+use log::debug;
+
+use crate::aoc::{Context, Day};
+use crate::aoc::crosscheck::Alternative;
+use crate::aoc::runner::{Part, SolveError};
+use crate::aoc::tools::interval::{IntervalSet, PiecewiseMap};
+use crate::aoc::tools::parse::{Field, ParseError, Pattern};
+use crate::aoc::tools::prop::Gen;
+
+/// Number of seeds a worker maps between progress updates and cancellation checks
+const PROGRESS_CHUNK: i64 = 1 << 16;
+
+/// The maps of an almanac in the order they are applied
+const MAPS: [&str; 7] = ["seed-to-soil", "soil-to-fertilizer", "fertilizer-to-water", "water-to-light",
+    "light-to-temperature", "temperature-to-humidity", "humidity-to-location"];
 
 pub struct Day5 {
     input: String,
@@ -18,48 +31,115 @@ impl Day5 {
             input
         }
     }
-}
 
-impl Day for Day5 {
-    fn part1(&self) -> String {
-        let almanac = Almanac::from_str(&self.input).unwrap();
-
-        almanac.seeds.iter()
-            .map(|seed| almanac.seed_to_location(seed))
-            .min().expect("No seeds found")
-            .to_string()
+    pub fn get_almanac(&self) -> Almanac {
+        Almanac::from_str(&self.input).unwrap_or_else(|e| panic!("Invalid almanac: {}", e))
+    }
+
+    /// A random almanac whose seed ranges and maps grow with the size, small sizes keep brute force fast.
+    /// The source ranges of each map are disjoint as every source has a single destination.
+    pub fn generate(g: &mut Gen) -> String {
+        let limit = 5 * g.size() as i64;
+        let count = 1 + g.size() / 4;
+        let seeds = g.vec(1..=count, |g| format!("{} {}", g.range(0..=limit), g.range(1..=limit)));
+        let mut out = format!("seeds: {}\n", seeds.join(" "));
+        for name in MAPS {
+            let mut start = g.range(0..=limit);
+            let ranges = g.vec(1..=count, |g| {
+                let (source, len) = (start + g.range(0..=5), g.range(1..=limit));
+                start = source + len;
+                format!("{} {} {}", g.range(0..=2 * limit), source, len)
+            });
+            out.push_str(&format!("\n{} map:\n{}\n", name, ranges.join("\n")));
+        }
+        out
     }
 
-    /// Very slow, longest worker needs about 30 minutes
-    fn part2(&self) -> String {
-        let almanac = Almanac::from_str(&self.input).unwrap();
+    /// Maps every single seed, kept to cross-check the interval based part 2.
+    /// Very slow, longest worker needs about 30 minutes.
+    /// Workers poll the cancellation token and stop early if the runner gives up.
+    pub fn part2_brute_force(&self, ctx: &Context) -> Result<String, SolveError> {
+        let almanac = self.get_almanac();
         let ranges = almanac.seed_range_map.clone().get_source_ranges();
 
-        thread::scope(|scope| {
+        let total = ranges.iter().map(|r| r.end - r.start).sum::<i64>();
+        ctx.progress.set_total(total as u64);
+        ctx.progress.set_message(&format!("{} workers", ranges.len()));
+
+        let almanac = &almanac;
+        let minimum = thread::scope(|scope| {
             let handles: Vec<_> = ranges.iter()
                 .map(|r|
-                    scope.spawn(|| {
+                    scope.spawn(move || {
                         let timer = std::time::Instant::now();
-                        println!("Starting thread");
-                        let res = r.clone()
-                            .map(|s| almanac.seed_to_location(&Seed(s)))
-                            .min();
-                        println!("Thread finished after {:?}", timer.elapsed());
+                        debug!("Starting worker for seeds {:?}", r);
+                        let mut res = None;
+                        let mut start = r.start;
+                        while start < r.end && !ctx.cancel.is_cancelled() {
+                            let end = min(start + PROGRESS_CHUNK, r.end);
+                            res = (start..end)
+                                .map(|s| almanac.seed_to_location(&Seed(s)))
+                                .chain(res)
+                                .min();
+                            ctx.progress.inc((end - start) as u64);
+                            start = end;
+                        }
+                        debug!("Worker for seeds {:?} finished after {:?}", r, timer.elapsed());
                         res
                     })
                 )
                 .collect();
 
             handles.into_iter()
-                .map(|handle| handle.join().unwrap())
+                .filter_map(|handle| handle.join().unwrap())
                 .min()
-                .unwrap().expect("No seeds found")
-                .to_string()
-        })
+        });
+
+        ctx.cancel.check()?;
+        Ok(minimum.ok_or_else(no_seeds)?.to_string())
+    }
+}
+
+/// Part 2 pairs the seed numbers to ranges, an odd number or only empty ranges leave no seeds for it.
+fn no_seeds() -> SolveError {
+    SolveError::NoSolution("No seeds found".to_string())
+}
+
+impl Day for Day5 {
+    fn part1(&self) -> String {
+        self.part1_with(&Context::default()).unwrap_or_else(|e| panic!("Invalid seeds: {}", e))
+    }
+
+    fn part2(&self) -> String {
+        self.part2_with(&Context::default()).unwrap_or_else(|e| panic!("Invalid seed ranges: {}", e))
+    }
+
+    fn part1_with(&self, _ctx: &Context) -> Result<String, SolveError> {
+        let almanac = self.get_almanac();
+
+        let minimum = almanac.seeds.iter()
+            .map(|seed| almanac.seed_to_location(seed))
+            .min().ok_or_else(no_seeds)?;
+        Ok(minimum.to_string())
+    }
+
+    fn part2_with(&self, _ctx: &Context) -> Result<String, SolveError> {
+        let almanac = self.get_almanac();
+
+        let minimum = almanac.seed_set_to_locations(&almanac.seed_set()).min().ok_or_else(no_seeds)?;
+        Ok(minimum.to_string())
+    }
+
+    fn alternatives(&self) -> Vec<Alternative<'_>> {
+        vec![Alternative::new(Part::Two, "brute force", |ctx| self.part2_brute_force(ctx))]
+    }
+
+    fn validate(&self) -> Result<(), ParseError> {
+        Almanac::from_str(&self.input).map(|_| ())
     }
 }
 
-trait Id
+pub trait Id
     where Self: Sized {
     fn raw(&self) -> i64;
 
@@ -74,8 +154,8 @@ trait Id
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd)]
-struct Seed(i64);
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd, Debug)]
+pub struct Seed(pub i64);
 
 impl Id for Seed {
     fn raw(&self) -> i64 {
@@ -93,8 +173,8 @@ impl Display for Seed {
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd)]
-struct Soil(i64);
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd, Debug)]
+pub struct Soil(pub i64);
 
 impl Display for Soil {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -112,8 +192,8 @@ impl Id for Soil {
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd)]
-struct Fertilizer(i64);
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd, Debug)]
+pub struct Fertilizer(pub i64);
 
 impl Display for Fertilizer {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
@@ -132,8 +212,8 @@ impl Id for Fertilizer {
 }
 
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd)]
-struct Water(i64);
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd, Debug)]
+pub struct Water(pub i64);
 
 impl Id for Water {
     fn raw(&self) -> i64 {
@@ -151,8 +231,8 @@ impl Display for Water {
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd)]
-struct Light(i64);
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd, Debug)]
+pub struct Light(pub i64);
 
 impl Id for Light {
     fn raw(&self) -> i64 {
@@ -170,8 +250,8 @@ impl Display for Light {
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd)]
-struct Temperature(i64);
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd, Debug)]
+pub struct Temperature(pub i64);
 
 impl Id for Temperature {
     fn raw(&self) -> i64 {
@@ -189,8 +269,8 @@ impl Display for Temperature {
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd)]
-struct Humidity(i64);
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd, Debug)]
+pub struct Humidity(pub i64);
 
 impl Id for Humidity {
     fn raw(&self) -> i64 {
@@ -208,8 +288,8 @@ impl Display for Humidity {
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd)]
-struct Location(i64);
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Ord, PartialOrd, Debug)]
+pub struct Location(pub i64);
 
 impl Id for Location {
     fn raw(&self) -> i64 {
@@ -227,8 +307,8 @@ impl Display for Location {
     }
 }
 
-struct RangeMap<S, D> {
-    map: Vec<(S, D, i64)>,
+pub struct RangeMap<S, D> {
+    pub map: Vec<(S, D, i64)>,
 }
 
 impl<S, D> Clone for RangeMap<S, D>
@@ -242,24 +322,25 @@ impl<S, D> Clone for RangeMap<S, D>
 
 impl<S, D> RangeMap<S, D>
     where S: Eq + Hash + PartialOrd + Id, D: Id {
-    fn new(block: &Vec<String>, fs: fn(i64) -> S, fd: fn(i64) -> D) -> RangeMap<S, D>
-        where S: Eq + Hash {
+    /// Parses the lines of a map block after its header, each line is a destination, a source and a length.
+    pub fn parse(lines: &[Field], fs: fn(i64) -> S, fd: fn(i64) -> D) -> Result<RangeMap<S, D>, ParseError> {
         let mut map = Vec::new();
-        for line in block {
-            let mut iter = line.split_whitespace();
-            let dest_start = iter.next().unwrap().parse::<i64>().unwrap();
-            let source_start = iter.next().unwrap().parse::<i64>().unwrap();
-            let range = iter.next().unwrap().parse::<i64>().unwrap();
-            let tuple = (fs(source_start), fd(dest_start), range);
-            map.push(tuple);
+        for line in lines {
+            let [dest_start, source_start, range] = parse_ids(*line)?[..] else {
+                return Err(line.error(format!("Expected destination, source and length, found '{}'", line.text)));
+            };
+            for start in [dest_start, source_start] {
+                fits(*line, start, range)?;
+            }
+            map.push((fs(source_start), fd(dest_start), range));
         }
 
-        RangeMap {
+        Ok(RangeMap {
             map
-        }
+        })
     }
 
-    fn get(&self, source: &S) -> Option<D> {
+    pub fn get(&self, source: &S) -> Option<D> {
         self.map.iter()
             // Find the correct range that contains the source
             .filter(|(s, _, r)| {
@@ -271,57 +352,62 @@ impl<S, D> RangeMap<S, D>
                 d.sub(s.raw()).add(source.raw())
             }).next()
     }
-    fn get_source_ranges(&self) -> Vec<Range<i64>> {
+    pub fn get_source_ranges(&self) -> Vec<Range<i64>> {
         self.map.iter()
             .map(|(s, _, r)| {
                 let raw_s = s.raw();
                 raw_s..raw_s + r
             }).collect()
     }
+
+    /// The same rules on raw ids, to map whole intervals of sources at once.
+    pub fn to_piecewise(&self) -> PiecewiseMap {
+        PiecewiseMap::new(self.map.iter().map(|(s, d, r)| (s.raw(), d.raw(), *r)))
+    }
 }
 
-struct Almanac {
-    seeds: Vec<Seed>,
-    seed_range_map: RangeMap<Seed, Seed>,
-    seed_to_soil_map: RangeMap<Seed, Soil>,
-    soil_to_fertilizer_map: RangeMap<Soil, Fertilizer>,
-    fertilizer_to_water_map: RangeMap<Fertilizer, Water>,
-    water_to_light_map: RangeMap<Water, Light>,
-    light_to_temperature_map: RangeMap<Light, Temperature>,
-    temperature_to_humidity_map: RangeMap<Temperature, Humidity>,
-    humidity_to_location_map: RangeMap<Humidity, Location>,
+pub struct Almanac {
+    pub seeds: Vec<Seed>,
+    pub seed_range_map: RangeMap<Seed, Seed>,
+    pub seed_to_soil_map: RangeMap<Seed, Soil>,
+    pub soil_to_fertilizer_map: RangeMap<Soil, Fertilizer>,
+    pub fertilizer_to_water_map: RangeMap<Fertilizer, Water>,
+    pub water_to_light_map: RangeMap<Water, Light>,
+    pub light_to_temperature_map: RangeMap<Light, Temperature>,
+    pub temperature_to_humidity_map: RangeMap<Temperature, Humidity>,
+    pub humidity_to_location_map: RangeMap<Humidity, Location>,
 }
 
 impl Almanac {
-    fn seed_to_soil(&self, seed: &Seed) -> Soil {
-        self.seed_to_soil_map.get(&seed).unwrap_or(Soil(seed.0)).clone()
+    pub fn seed_to_soil(&self, seed: &Seed) -> Soil {
+        self.seed_to_soil_map.get(seed).unwrap_or(Soil(seed.0))
     }
 
-    fn soil_to_fertilizer(&self, soil: &Soil) -> Fertilizer {
-        self.soil_to_fertilizer_map.get(&soil).unwrap_or(Fertilizer(soil.0)).clone()
+    pub fn soil_to_fertilizer(&self, soil: &Soil) -> Fertilizer {
+        self.soil_to_fertilizer_map.get(soil).unwrap_or(Fertilizer(soil.0))
     }
 
-    fn fertilizer_to_water(&self, fertilizer: &Fertilizer) -> Water {
-        self.fertilizer_to_water_map.get(&fertilizer).unwrap_or(Water(fertilizer.0)).clone()
+    pub fn fertilizer_to_water(&self, fertilizer: &Fertilizer) -> Water {
+        self.fertilizer_to_water_map.get(fertilizer).unwrap_or(Water(fertilizer.0))
     }
 
-    fn water_to_light(&self, water: &Water) -> Light {
-        self.water_to_light_map.get(&water).unwrap_or(Light(water.0)).clone()
+    pub fn water_to_light(&self, water: &Water) -> Light {
+        self.water_to_light_map.get(water).unwrap_or(Light(water.0))
     }
 
-    fn light_to_temperature(&self, light: &Light) -> Temperature {
-        self.light_to_temperature_map.get(&light).unwrap_or(Temperature(light.0)).clone()
+    pub fn light_to_temperature(&self, light: &Light) -> Temperature {
+        self.light_to_temperature_map.get(light).unwrap_or(Temperature(light.0))
     }
 
-    fn temperature_to_humidity(&self, temperature: &Temperature) -> Humidity {
-        self.temperature_to_humidity_map.get(&temperature).unwrap_or(Humidity(temperature.0)).clone()
+    pub fn temperature_to_humidity(&self, temperature: &Temperature) -> Humidity {
+        self.temperature_to_humidity_map.get(temperature).unwrap_or(Humidity(temperature.0))
     }
 
-    fn humidity_to_location(&self, humidity: &Humidity) -> Location {
-        self.humidity_to_location_map.get(&humidity).unwrap_or(Location(humidity.0)).clone()
+    pub fn humidity_to_location(&self, humidity: &Humidity) -> Location {
+        self.humidity_to_location_map.get(humidity).unwrap_or(Location(humidity.0))
     }
 
-    fn seed_to_location(&self, seed: &Seed) -> Location {
+    pub fn seed_to_location(&self, seed: &Seed) -> Location {
         let soil = self.seed_to_soil(seed);
         let fertilizer = self.soil_to_fertilizer(&soil);
         let water = self.fertilizer_to_water(&fertilizer);
@@ -330,54 +416,100 @@ impl Almanac {
         let humidity = self.temperature_to_humidity(&temperature);
         self.humidity_to_location(&humidity)
     }
+
+    /// All seeds covered by the seed ranges of part 2.
+    pub fn seed_set(&self) -> IntervalSet {
+        IntervalSet::from_ranges(self.seed_range_map.get_source_ranges())
+    }
+
+    /// Maps a set of seed ids to the set of their location ids, interval by interval.
+    pub fn seed_set_to_locations(&self, seeds: &IntervalSet) -> IntervalSet {
+        [
+            self.seed_to_soil_map.to_piecewise(),
+            self.soil_to_fertilizer_map.to_piecewise(),
+            self.fertilizer_to_water_map.to_piecewise(),
+            self.water_to_light_map.to_piecewise(),
+            self.light_to_temperature_map.to_piecewise(),
+            self.temperature_to_humidity_map.to_piecewise(),
+            self.humidity_to_location_map.to_piecewise(),
+        ].iter()
+            .fold(seeds.clone(), |set, map| map.map_set(&set))
+    }
+}
+
+/// Ids and lengths are never negative.
+fn parse_ids(line: Field) -> Result<Vec<i64>, ParseError> {
+    line.words().iter()
+        .map(|word| match word.parse::<i64>()? {
+            id if id < 0 => Err(word.error(format!("Expected an id or length of at least 0, found {}", id))),
+            id => Ok(id),
+        })
+        .collect()
+}
+
+/// A range must end within `i64`, so mapping its ids cannot overflow.
+fn fits(line: Field, start: i64, len: i64) -> Result<(), ParseError> {
+    match start.checked_add(len) {
+        Some(_) => Ok(()),
+        None => Err(line.error(format!("Range of {} ids from {} is too large", len, start))),
+    }
 }
 
 impl FromStr for Almanac {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let blocks = read_blocks(s, Some("map"));
-        let seeds = blocks[0][0].strip_prefix("seeds: ").unwrap()
-            .split(" ")
-            .map(|s| Seed(s.parse::<i64>().unwrap()))
+        let input = Field::new(s);
+        let blocks = input.blocks();
+        if blocks.len() != MAPS.len() + 1 {
+            return Err(input.error(format!("Expected seeds and {} maps, found {} blocks", MAPS.len(), blocks.len())));
+        }
+        let seeds_line = blocks[0].matches(&Pattern::new("seeds: {seeds}"))?.get("seeds");
+        let seeds = parse_ids(seeds_line)?.into_iter()
+            .map(Seed)
             .collect::<Vec<Seed>>();
 
         // Make a vector of pairs from the vector of seeds
         //
         let seed_pairs = seeds.iter().step_by(2)
             .zip(seeds.iter().skip(1).step_by(2))
-            .map(|(s1, s2)| (s1.clone(), s1.clone(), s2.raw()))
-            .collect();
+            .map(|(s1, s2)| fits(seeds_line, s1.raw(), s2.raw()).map(|_| (*s1, *s1, s2.raw())))
+            .collect::<Result<_, _>>()?;
 
         let seed_range_map = RangeMap {
             map: seed_pairs,
         };
 
-        let seed_to_soil_map = RangeMap::new(&blocks[1], Seed, Soil);
-        let soil_to_fertilizer_map = RangeMap::new(&blocks[2], Soil, Fertilizer);
-        let fertilizer_to_water_map = RangeMap::new(&blocks[3], Fertilizer, Water);
-        let water_to_light_map = RangeMap::new(&blocks[4], Water, Light);
-        let light_to_temperature_map = RangeMap::new(&blocks[5], Light, Temperature);
-        let temperature_to_humidity_map = RangeMap::new(&blocks[6], Temperature, Humidity);
-        let humidity_to_location_map = RangeMap::new(&blocks[7], Humidity, Location);
+        // The lines of each map after its header
+        let mut maps = Vec::new();
+        for (block, name) in blocks[1..].iter().zip(MAPS) {
+            let lines = block.lines();
+            let header = lines[0].matches(&Pattern::new("{name} map:"))?.get("name");
+            if header.text != name {
+                return Err(header.error(format!("Expected the {} map, found '{}'", name, header.text)));
+            }
+            maps.push(lines[1..].to_vec());
+        }
 
         Ok(Almanac {
             seeds,
             seed_range_map,
-            seed_to_soil_map,
-            soil_to_fertilizer_map,
-            fertilizer_to_water_map,
-            water_to_light_map,
-            light_to_temperature_map,
-            temperature_to_humidity_map,
-            humidity_to_location_map,
+            seed_to_soil_map: RangeMap::parse(&maps[0], Seed, Soil)?,
+            soil_to_fertilizer_map: RangeMap::parse(&maps[1], Soil, Fertilizer)?,
+            fertilizer_to_water_map: RangeMap::parse(&maps[2], Fertilizer, Water)?,
+            water_to_light_map: RangeMap::parse(&maps[3], Water, Light)?,
+            light_to_temperature_map: RangeMap::parse(&maps[4], Light, Temperature)?,
+            temperature_to_humidity_map: RangeMap::parse(&maps[5], Temperature, Humidity)?,
+            humidity_to_location_map: RangeMap::parse(&maps[6], Humidity, Location)?,
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::aoc::Day;
+    use crate::aoc::{Context, Day};
+    use crate::aoc::runner::SolveError;
+    use crate::aoc::tools::prop::{assert_property, ensure_eq};
 
     const INPUT: &str = r#"
         seeds: 79 14 55 13
@@ -427,4 +559,43 @@ mod tests {
     fn test_part2() {
         assert_eq!(day().part2(), "46");
     }
+
+    #[test]
+    fn test_part2_brute_force() {
+        assert_eq!(day().part2_brute_force(&Context::default()), Ok("46".to_string()));
+    }
+
+    #[test]
+    fn test_no_seeds() {
+        let expected = Err(SolveError::NoSolution("No seeds found".to_string()));
+        // Part 1 reads the same numbers as single seeds
+        for (seeds, part1) in [("seeds: 79", "82"), ("seeds: 79 0 55 0", "22")] {
+            let day = super::Day5::new(INPUT.replace("seeds: 79 14 55 13", seeds));
+            assert_eq!(day.part1_with(&Context::default()), Ok(part1.to_string()), "{}", seeds);
+            assert_eq!(day.part2_with(&Context::default()), expected, "{}", seeds);
+            assert_eq!(day.part2_brute_force(&Context::default()), expected, "{}", seeds);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(day().validate(), Ok(()));
+        let renamed = INPUT.replace("light-to-temperature map:", "light-to-heat map:");
+        assert_eq!(super::Day5::new(renamed).validate().unwrap_err().message,
+                   "Expected the light-to-temperature map, found 'light-to-heat'");
+        let error = super::Day5::new(INPUT.replace("50 98 2", "50 -98 2")).validate().unwrap_err();
+        assert_eq!(error.message, "Expected an id or length of at least 0, found -98");
+        let error = super::Day5::new(INPUT.replace("50 98 2", "50 98 9223372036854775807")).validate().unwrap_err();
+        assert_eq!(error.message, "Range of 9223372036854775807 ids from 50 is too large");
+        let error = super::Day5::new(INPUT.split("\n\n").take(3).collect::<Vec<_>>().join("\n\n")).validate().unwrap_err();
+        assert_eq!(error.to_string(), "line 1, column 1: Expected seeds and 7 maps, found 3 blocks");
+    }
+
+    #[test]
+    fn test_intervals_match_brute_force() {
+        assert_property("lowest location", super::Day5::generate, |input| {
+            let day = super::Day5::new(input.clone());
+            ensure_eq(Ok(day.part2()), day.part2_brute_force(&Context::default()), "lowest locations")
+        });
+    }
 }
@@ -1,7 +1,16 @@
-use std::str::FromStr;
+use crate::aoc::{Context, Day};
+use crate::aoc::cancel::Cancelled;
+use crate::aoc::crosscheck::Alternative;
+use crate::aoc::runner::{Part, SolveError};
+use crate::aoc::tools::checked::checked_product;
+use crate::aoc::tools::parse::{Field, ParseError, Pattern};
+use crate::aoc::tools::prop::Gen;
 
-use crate::aoc::Day;
-use crate::aoc::tools::{read_columns, read_lines};
+/// Digits of all generated durations together, the kerned distance has at most twice as many.
+const KERNED_DIGITS: usize = 8;
+
+/// Number of speeds the brute force tries between cancellation checks
+const BATCH_SIZE: u64 = 1 << 20;
 
 pub struct Day6 {
     input: String,
@@ -12,70 +21,154 @@ impl Day6 {
         Day6 { input }
     }
 
-    fn get_races(&self) -> Vec<Race> {
-        read_columns(&self.input).iter().skip(1)
-            .map(|c| Race::new(
-                u64::from_str(&c[0]).unwrap(),
-                u64::from_str(&c[1]).unwrap(),
-            ))
+    /// The values of the time and distance rows, both have the same number of words.
+    fn parse_rows(input: &str) -> Result<(Field<'_>, Field<'_>), ParseError> {
+        let field = Field::new(input);
+        let lines = field.lines();
+        let [times, distances] = lines[..] else {
+            return Err(field.error(format!("Expected a time and a distance row, found {} lines", lines.len())));
+        };
+        let times = times.matches(&Pattern::new("Time:{times}"))?.get("times");
+        let distances = distances.matches(&Pattern::new("Distance:{distances}"))?.get("distances");
+        if times.words().len() != distances.words().len() {
+            return Err(distances.error(format!("Expected {} distances, found {}", times.words().len(), distances.words().len())));
+        }
+        Ok((times, distances))
+    }
+
+    pub fn parse_races(input: &str) -> Result<Vec<Race>, ParseError> {
+        let (times, distances) = Day6::parse_rows(input)?;
+        times.list::<u64>()?.into_iter()
+            .zip(distances.list::<u64>()?)
+            .map(|(duration, record)| Ok(Race::new(duration, record)))
             .collect()
     }
 
-    fn get_race(&self) -> Race {
-        let rows = read_lines(&self.input);
+    pub fn parse_race(input: &str) -> Result<Race, ParseError> {
+        // The spaces between the digits are bad kerning, a row is a single number
+        let kerned = |row: Field| {
+            let digits = row.words().iter().map(|w| w.text).collect::<String>();
+            digits.parse::<u64>().map_err(|_| row.error(format!("Expected u64, found '{}'", digits)))
+        };
+        let (times, distances) = Day6::parse_rows(input)?;
+        Ok(Race::new(kerned(times)?, kerned(distances)?))
+    }
+
+    pub fn get_races(&self) -> Vec<Race> {
+        Day6::parse_races(&self.input).unwrap_or_else(|e| panic!("Invalid races: {}", e))
+    }
 
-        let res: Vec<u64> = rows.iter()
-            .map(|r| r.split_whitespace().skip(1)
-                .fold("".to_string(), |acc, s| acc + s))
-            .map(|s| u64::from_str(&s).unwrap())
-            .collect();
+    /// Random races, some records cannot be beaten, larger sizes give more and longer races. The durations have at
+    /// most `KERNED_DIGITS` digits together, so the kerned race fits a `u64` and is short enough for brute force,
+    /// and so does the product of the wins.
+    pub fn generate(g: &mut Gen) -> String {
+        let max_duration = (3 * g.size()).clamp(1, 9_999) as i64;
+        let width = max_duration.to_string().len();
+        let max_races = (1 + g.size() / 8).min(KERNED_DIGITS / width);
+        let races = g.vec(max_races.min(1 + g.size() / 16)..=max_races, |g| {
+            let duration = g.range(1..=max_duration);
+            (duration, g.range(0..=duration * duration / 4 + 1))
+        });
+        let row = |values: Vec<i64>| values.iter().map(|v| format!(" {:3}", v)).collect::<String>();
+        format!("Time:    {}\nDistance:{}",
+                row(races.iter().map(|r| r.0).collect()),
+                row(races.iter().map(|r| r.1).collect()))
+    }
 
-        Race::new(res[0], res[1])
+    pub fn get_race(&self) -> Race {
+        Day6::parse_race(&self.input).unwrap_or_else(|e| panic!("Invalid race: {}", e))
     }
 }
 
-struct Race {
-    duration: u64,
-    record: u64,
+pub struct Race {
+    pub duration: u64,
+    pub record: u64,
 }
 
 impl Race {
-    fn new(duration: u64, record: u64) -> Self {
+    pub fn new(duration: u64, record: u64) -> Self {
         Race {
             duration,
             record,
         }
     }
 
-    fn possible_wins(&self) -> u64 {
+    /// Tries every speed, the distances are computed in `u128` as they may not fit a `u64`.
+    pub fn possible_wins(&self, ctx: &Context) -> Result<u64, Cancelled> {
         let mut wins = 0;
         for speed in 0..=self.duration {
-            let distance = speed * (self.duration - speed);
-            if distance > self.record {
+            if speed.is_multiple_of(BATCH_SIZE) {
+                ctx.cancel.check()?;
+            }
+            let distance = speed as u128 * (self.duration - speed) as u128;
+            if distance > self.record as u128 {
                 wins += 1;
             }
         }
-        wins
+        Ok(wins)
+    }
+
+    /// The speeds `s` with `s * (duration - s) > record` lie strictly between the roots of a quadratic.
+    /// The integer square root gives a first guess for the slowest winning speed, the rest is symmetric.
+    pub fn possible_wins_quadratic(&self) -> u64 {
+        let (duration, record) = (self.duration as u128, self.record as u128);
+        let distance = |speed: u128| speed * (duration - speed);
+        let discriminant = match (duration * duration).checked_sub(4 * record) {
+            Some(d) if d > 0 => d,
+            _ => return 0,
+        };
+        let mut slowest = (duration - discriminant.isqrt().min(duration)) / 2;
+        while slowest > 0 && distance(slowest - 1) > record {
+            slowest -= 1;
+        }
+        while slowest <= duration / 2 && distance(slowest) <= record {
+            slowest += 1;
+        }
+        if slowest > duration / 2 {
+            return 0;
+        }
+        (duration + 1 - 2 * slowest) as u64
     }
 }
 
 impl Day for Day6 {
     fn part1(&self) -> String {
-        self.get_races().iter()
-            .map(|r| r.possible_wins())
-            .product::<u64>()
-            .to_string()
+        self.part1_with(&Context::default()).unwrap_or_else(|e| panic!("Product of the wins does not fit: {}", e))
     }
 
     fn part2(&self) -> String {
-        self.get_race().possible_wins().to_string()
+        self.get_race().possible_wins_quadratic().to_string()
+    }
+
+    fn part1_with(&self, _ctx: &Context) -> Result<String, SolveError> {
+        let wins = self.get_races().into_iter().map(|r| r.possible_wins_quadratic());
+        Ok(checked_product(wins)?.to_string())
+    }
+
+    /// Trying every speed takes as long as the race, so the brute force only checks the closed form on short races.
+    fn alternatives(&self) -> Vec<Alternative<'_>> {
+        vec![
+            Alternative::new(Part::One, "brute force", |ctx| {
+                let wins = self.get_races().iter()
+                    .map(|r| r.possible_wins(ctx))
+                    .collect::<Result<Vec<u64>, Cancelled>>()?;
+                Ok(checked_product(wins)?.to_string())
+            }),
+            Alternative::new(Part::Two, "brute force", |ctx| Ok(self.get_race().possible_wins(ctx)?.to_string())),
+        ]
+    }
+
+    fn validate(&self) -> Result<(), ParseError> {
+        Day6::parse_races(&self.input)?;
+        Day6::parse_race(&self.input).map(|_| ())
     }
 }
 
 
 #[cfg(test)]
 mod tests {
-    use crate::aoc::Day;
+    use crate::aoc::{Context, Day};
+    use crate::aoc::tools::prop::{assert_property, ensure_eq, Gen};
 
     const INPUT: &str = r#"
         Time:      7  15   30
@@ -95,4 +188,54 @@ mod tests {
     fn test_part2() {
         assert_eq!(day().part2(), "71503");
     }
+
+    #[test]
+    fn test_possible_wins_quadratic() {
+        let wins: Vec<u64> = day().get_races().iter().map(|r| r.possible_wins_quadratic()).collect();
+        assert_eq!(wins, vec![4, 8, 9]);
+        assert_eq!(day().get_race().possible_wins_quadratic(), 71503);
+        assert_eq!(super::Race::new(4, 4).possible_wins_quadratic(), 0);
+        assert_eq!(super::Race::new(1, 0).possible_wins_quadratic(), 0);
+    }
+
+    #[test]
+    fn test_long_races() {
+        let day = super::Day6::new("Time: 9999999999999999999\nDistance: 1".to_string());
+        assert_eq!(day.part2(), "9999999999999999998");
+        let day = super::Day6::new("Time: 9999999999 9999999999\nDistance: 0 0".to_string());
+        let error = day.part1_with(&Context::default()).unwrap_err();
+        assert_eq!(error.to_string(), "9999999998 * 9999999998 overflows u64");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(day().validate(), Ok(()));
+        let error = super::Day6::new("Time: 7 15\nDistance: 9".to_string()).validate().unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 11: Expected 2 distances, found 1");
+        let error = super::Day6::new("Time: 7".to_string()).validate().unwrap_err();
+        assert_eq!(error.message, "Expected a time and a distance row, found 1 lines");
+        let error = super::Day6::new("Time: 7 99999999999999999999\nDistance: 9 1".to_string()).validate().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 9));
+    }
+
+    #[test]
+    fn test_generated_races_fit() {
+        for seed in 0..200 {
+            let input = super::Day6::generate(&mut Gen::new(seed, 1_000_000));
+            let race = super::Day6::parse_race(&input).unwrap();
+            assert!(race.duration < 10u64.pow(super::KERNED_DIGITS as u32), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_win_counts_agree() {
+        assert_property("win counts", super::Day6::generate, |input| {
+            let day = super::Day6::new(input.clone());
+            for race in day.get_races().iter().chain([&day.get_race()]) {
+                ensure_eq(race.possible_wins(&Context::default()).unwrap(), race.possible_wins_quadratic(),
+                          &format!("wins of the {} ms race with record {}", race.duration, race.record))?;
+            }
+            Ok(())
+        });
+    }
 }
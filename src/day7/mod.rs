@@ -1,27 +1,30 @@
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
-use crate::aoc::Day;
-use crate::aoc::tools::read_lines;
+use crate::aoc::{Context, Day};
+use crate::aoc::runner::SolveError;
+use crate::aoc::tools::checked::{checked_dot, convert, Overflow};
+use crate::aoc::tools::parse::{Field, parse_lines, ParseError, Pattern};
+use crate::aoc::tools::prop::Gen;
 
 #[derive(Eq, Clone)]
-struct Hand {
-    cards: Vec<Card>,
-    bid: u32,
-    joker: bool,
+pub struct Hand {
+    pub cards: Vec<Card>,
+    pub bid: u32,
+    pub joker: bool,
 }
 
 impl Hand {
-    fn new(cards: Vec<Card>, bid: u32, joker: bool) -> Self {
+    pub fn new(cards: Vec<Card>, bid: u32, joker: bool) -> Self {
         Hand {
             cards,
             bid,
             joker,
         }
     }
-    fn get_type(&self) -> Type {
+    pub fn get_type(&self) -> Type {
         let mut cards = self.cards.clone();
         cards.sort();
 
@@ -44,7 +47,7 @@ impl Hand {
 
         let highest_entry = counts
             .iter()
-            .max_by(|a, b| a.1.cmp(&b.1))
+            .max_by(|a, b| a.1.cmp(b.1))
             .unwrap();
 
 
@@ -74,30 +77,14 @@ impl Display for Hand {
 impl PartialEq for Hand {
     fn eq(&self, other: &Self) -> bool {
         self.get_type() == other.get_type() &&
-            self.cards.len() == self.cards.len() &&
-            self.cards == self.cards
+            self.cards.len() == other.cards.len() &&
+            self.cards == other.cards
     }
 }
 
 impl PartialOrd for Hand {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let our_type = self.get_type();
-        let other_type = other.get_type();
-
-        match our_type.partial_cmp(&other_type) {
-            Some(Ordering::Equal) => {
-                for (card, card_other) in self.cards.iter().zip(&other.cards) {
-                    if card > card_other {
-                        return Some(Ordering::Greater);
-                    }
-                    if card < card_other {
-                        return Some(Ordering::Less);
-                    }
-                }
-                Some(Ordering::Equal)
-            }
-            o => o,
-        }
+        Some(self.cmp(other))
     }
 }
 
@@ -147,8 +134,8 @@ impl Ord for Hand {
     }
 }
 
-#[derive(PartialOrd, Ord, PartialEq, Eq)]
-enum Type {
+#[derive(PartialOrd, Ord, PartialEq, Eq, Debug)]
+pub enum Type {
     HighCard = 0,
     OnePair = 1,
     TwoPair = 2,
@@ -158,8 +145,8 @@ enum Type {
     FiveOfAKind = 6,
 }
 
-#[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Hash)]
-enum Symbol {
+#[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Hash, Debug)]
+pub enum Symbol {
     Joker = 0,
     Two = 1,
     Three = 2,
@@ -220,13 +207,15 @@ impl FromStr for Symbol {
 }
 
 #[derive(Eq, Clone)]
-struct Card {
-    symbol: Symbol,
+pub struct Card {
+    pub symbol: Symbol,
 }
 
 impl Card {
-    fn new(symbol: char, joker: bool) -> Self {
-        let mut s = Symbol::from_str(&symbol.to_string()).unwrap();
+    /// Panics for an invalid label, `Day7::parse_hand` checks them first.
+    pub fn new(symbol: char, joker: bool) -> Self {
+        let mut s = Symbol::from_str(&symbol.to_string())
+            .unwrap_or_else(|_| panic!("Invalid card '{}'", symbol));
         if s == Symbol::Jack && joker {
             s = Symbol::Joker;
         }
@@ -238,7 +227,7 @@ impl Card {
 
 impl Display for Card {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.symbol.to_string())
+        write!(f, "{}", self.symbol)
     }
 }
 
@@ -250,7 +239,7 @@ impl PartialEq for Card {
 
 impl PartialOrd for Card {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.symbol.partial_cmp(&other.symbol)
+        Some(self.cmp(other))
     }
 }
 
@@ -269,45 +258,84 @@ impl Day7 {
         Day7 { input }
     }
 
-    fn get_hands(&self, joker: bool) -> Vec<Hand> {
-        read_lines(&self.input).iter()
-            .map(|r| r.split_whitespace().collect())
-            .map(|h: Vec<&str>|
-                Hand::new(
-                    h[0].chars()
-                        .map(|c| Card::new(c, joker))
-                        .collect(),
-                    u32::from_str(&h[1]).unwrap(),
-                    joker,
-                )
-            ).collect()
+    /// Random distinct hands with bids. Each hand draws from a few card labels, so pairs and more are common.
+    pub fn generate(g: &mut Gen) -> String {
+        const LABELS: &[u8] = b"23456789TJQKA";
+        let count = g.usize(g.scaled());
+        let mut hands: Vec<String> = Vec::new();
+        let mut seen = HashSet::new();
+        for _ in 0..4 * count {
+            let labels = g.vec(1..=5, |g| *g.choose(LABELS).unwrap() as char);
+            let hand: String = (0..5).map(|_| *g.choose(&labels).unwrap()).collect();
+            if seen.insert(hand.clone()) {
+                hands.push(hand);
+            }
+            if hands.len() == count {
+                break;
+            }
+        }
+        hands.iter()
+            .map(|hand| format!("{} {}", hand, g.range(1..=1000)))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// Five card labels and a bid.
+    pub fn parse_hand(line: Field, joker: bool) -> Result<Hand, ParseError> {
+        let hand = line.matches(&Pattern::new("{cards} {bid}"))?;
+        let cards = hand.get("cards");
+        let count = cards.text.chars().count();
+        if count != 5 {
+            return Err(cards.error(format!("Expected 5 cards, found {}", count)));
+        }
+        if let Some((i, c)) = cards.text.char_indices().find(|(_, c)| Symbol::from_str(&c.to_string()).is_err()) {
+            return Err(cards.slice(i..i + c.len_utf8()).error(format!("Invalid card '{}'", c)));
+        }
+        Ok(Hand::new(cards.text.chars().map(|c| Card::new(c, joker)).collect(), hand.parse("bid")?, joker))
+    }
+
+    pub fn get_hands(&self, joker: bool) -> Vec<Hand> {
+        parse_lines(&self.input, |line| Day7::parse_hand(line, joker))
+            .unwrap_or_else(|e| panic!("Invalid hand: {}", e))
+    }
+
+    /// Sum of every bid times the rank of its hand.
+    pub fn winnings(&self, joker: bool) -> Result<u32, Overflow> {
+        let mut hands = self.get_hands(joker);
+        hands.sort();
+        let ranked = hands.iter().enumerate()
+            .map(|(i, h)| Ok((convert(i + 1)?, h.bid)))
+            .collect::<Result<Vec<(u32, u32)>, Overflow>>()?;
+        checked_dot(ranked)
     }
 }
 
 impl Day for Day7 {
     fn part1(&self) -> String {
-        let mut hands = self.get_hands(false);
-        hands.sort();
-        hands.iter().enumerate()
-            .map(|(i, h)| (i + 1) as u32 * h.bid)
-            .sum::<u32>()
-            .to_string()
+        self.part1_with(&Context::default()).unwrap_or_else(|e| panic!("Winnings do not fit: {}", e))
     }
 
     fn part2(&self) -> String {
-        let mut hands = self.get_hands(true);
-        hands.sort();
-        let value = hands.iter().enumerate()
-            .map(|(i, h)| (i + 1) as u32 * h.bid)
-            .sum::<u32>();
+        self.part2_with(&Context::default()).unwrap_or_else(|e| panic!("Winnings do not fit: {}", e))
+    }
+
+    fn part1_with(&self, _ctx: &Context) -> Result<String, SolveError> {
+        Ok(self.winnings(false)?.to_string())
+    }
+
+    fn part2_with(&self, _ctx: &Context) -> Result<String, SolveError> {
+        Ok(self.winnings(true)?.to_string())
+    }
 
-        value.to_string()
+    fn validate(&self) -> Result<(), ParseError> {
+        parse_lines(&self.input, |line| Day7::parse_hand(line, false)).map(|_| ())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::aoc::*;
+    use crate::aoc::tools::prop;
 
     use super::*;
 
@@ -333,6 +361,53 @@ mod tests {
         assert_eq!(day().part2(), "5905");
     }
 
+    #[test]
+    fn test_winnings_overflow() {
+        let d = Day7::new("AAAAA 4294967295\n22222 1".to_string());
+        let error = d.winnings(false).unwrap_err();
+        assert_eq!(error.to_string(), "2 * 4294967295 overflows u32");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(day().validate(), Ok(()));
+        let error = Day7::new("32T3K 765\nT5XJ5 684".to_string()).validate().unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 3: Invalid card 'X'");
+        let error = Day7::new("32T3 765".to_string()).validate().unwrap_err();
+        assert_eq!(error.message, "Expected 5 cards, found 4");
+        assert!(Day7::new("32T3K".to_string()).validate().is_err());
+    }
+
+    #[test]
+    fn test_order_does_not_matter() {
+        let shuffled = |g: &mut prop::Gen| {
+            let input = Day7::generate(g);
+            let mut lines: Vec<&str> = input.lines().collect();
+            for i in (1..lines.len()).rev() {
+                lines.swap(i, g.usize(0..=i));
+            }
+            (input.clone(), lines.join("\n"))
+        };
+        prop::assert_property("winnings of shuffled hands", shuffled, |(input, shuffled)| {
+            let (day, other) = (Day7::new(input.clone()), Day7::new(shuffled.clone()));
+            prop::ensure_eq(day.part1(), other.part1(), "part 1")?;
+            prop::ensure_eq(day.part2(), other.part2(), "part 2")
+        });
+    }
+
+    #[test]
+    fn test_jokers_never_weaken() {
+        prop::assert_property("jokers never weaken a hand", Day7::generate, |input| {
+            let day = Day7::new(input.clone());
+            for (plain, joker) in day.get_hands(false).iter().zip(day.get_hands(true)) {
+                if joker.get_type() < plain.get_type() {
+                    return Err(format!("{} is a {:?} with jokers and a {:?} without", plain, joker.get_type(), plain.get_type()));
+                }
+            }
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_day_1_full() {
         let input = read_input(7).unwrap();
@@ -373,4 +448,13 @@ mod tests {
         assert!(Type::FullHouse < Type::FourOfAKind);
         assert!(Type::FourOfAKind < Type::FiveOfAKind);
     }
+
+    #[test]
+    fn test_hand_equality() {
+        let hand = |cards: &str| Hand::new(cards.chars().map(|c| Card::new(c, false)).collect(), 1, false);
+        assert!(hand("32T3K") == hand("32T3K"));
+        // Hands of the same type are only equal with the same cards
+        assert!(hand("23456") != hand("34567"));
+        assert!(hand("32T3K") != hand("32T4K"));
+    }
 }
\ No newline at end of file
@@ -1,22 +1,30 @@
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::thread;
 
-use crate::aoc::Day;
+use log::{debug, trace};
+
+use crate::aoc::{Context, Day};
+use crate::aoc::crosscheck::Alternative;
+use crate::aoc::runner::{Part, SolveError};
+use crate::aoc::tools::cycle;
+use crate::aoc::tools::parse::{Field, ParseError, Pattern};
+use crate::aoc::tools::prop::Gen;
 
 pub struct Day8 {
     input: String,
 }
 
-struct Journey {
-    current: String,
-    instructions: Vec<Instruction>,
-    current_instruction: u32,
-    network: Network,
-    steps: u32,
+pub struct Journey {
+    pub current: String,
+    pub instructions: Vec<Instruction>,
+    pub current_instruction: u32,
+    pub network: Network,
+    pub steps: u64,
 }
 
 impl Journey {
-    fn new(instructions: Vec<Instruction>, network: Network) -> Self {
+    pub fn new(instructions: Vec<Instruction>, network: Network) -> Self {
         let start = String::from("AAA");
         Self {
             instructions,
@@ -27,7 +35,7 @@ impl Journey {
         }
     }
 
-    fn set_start(&mut self, start: String) {
+    pub fn set_start(&mut self, start: String) {
         self.current = start.clone();
     }
 
@@ -54,19 +62,19 @@ impl Journey {
         self.current_instruction += 1;
     }
 
-    fn is_end(&self) -> bool {
+    pub fn is_end(&self) -> bool {
         self.current.ends_with("Z")
     }
 }
 
-#[derive(Clone)]
-enum Instruction {
+#[derive(Clone, Debug, PartialEq)]
+pub enum Instruction {
     L,
     R,
 }
 
 impl Instruction {
-    fn new(input: &str) -> Self {
+    pub fn new(input: &str) -> Self {
         match input {
             "L" => Self::L,
             "R" => Self::R,
@@ -84,119 +92,154 @@ impl FromStr for Instruction {
 }
 
 #[derive(Clone)]
-struct Network {
-    nodes: Vec<Node>,
+pub struct Network {
+    pub nodes: Vec<Node>,
+    index: HashMap<String, usize>,
 }
 
 impl Network {
-    fn new(nodes: Vec<Node>) -> Self {
-        Self { nodes }
+    pub fn new(nodes: Vec<Node>) -> Self {
+        let index = nodes.iter()
+            .enumerate()
+            .map(|(i, n)| (n.id.clone(), i))
+            .collect();
+        Self { nodes, index }
     }
 
-    fn get_node(&self, id: &str) -> Option<&Node> {
-        self.nodes.iter()
-            .find(|n| n.id == id)
+    pub fn get_node(&self, id: &str) -> Option<&Node> {
+        self.index.get(id).map(|i| &self.nodes[*i])
     }
 }
 
 #[derive(Clone)]
-struct Node {
-    id: String,
-    left: String,
-    right: String,
+pub struct Node {
+    pub id: String,
+    pub left: String,
+    pub right: String,
 }
 
 impl Node {
-    fn new(id: String, left: String, right: String) -> Self {
+    pub fn new(id: String, left: String, right: String) -> Self {
         Self { id, left, right }
     }
 
-    fn is_start(&self) -> bool {
+    pub fn is_start(&self) -> bool {
         self.id.ends_with("A")
     }
 }
 
 impl Day8 {
-    pub(crate) fn new(input: String) -> Self {
+    pub fn new(input: String) -> Self {
         Self { input }
     }
 
-    fn split_input(s: &String) -> Vec<String> {
-        s
-            .trim()
-            .split("\n\n")
-            .map(|s| s.trim().to_string())
-            .collect()
+    /// A random network of ghost loops in random order. Ghost `k` walks from its start through a chain to its end
+    /// and from there back into the chain, the first ghost from `AAA` to `ZZZ`. Both branches of a node lead
+    /// to the same node, so every ghost ends on the length of its loop and all of them on the least common multiple.
+    pub fn generate(g: &mut Gen) -> String {
+        let instructions: String = g.vec(1..=g.size(), |g| *g.choose(&['L', 'R']).unwrap()).into_iter().collect();
+        let mut nodes = Vec::new();
+        // Ghosts are named A, C, E and so on up to W
+        for ghost in 0..g.usize(1..=(2 + g.size() / 8).min(12)) {
+            let name = (b'A' + ghost as u8 * 2) as char;
+            let end = if ghost == 0 { "ZZZ".to_string() } else { format!("{}{}Z", name, name) };
+            let mut chain = vec![format!("{}{}A", name, name)];
+            chain.extend((1..g.usize(1..=2 + g.size() / 4)).map(|i| format!("{}{:02}", name, i)));
+            chain.push(end);
+            // The end leads back to the node after the start, possibly itself
+            let back = chain[1].clone();
+            for (i, id) in chain.iter().enumerate() {
+                let next = chain.get(i + 1).unwrap_or(&back);
+                nodes.push(format!("{} = ({}, {})", id, next, next));
+            }
+        }
+        for i in (1..nodes.len()).rev() {
+            nodes.swap(i, g.usize(0..=i));
+        }
+        format!("{}\n\n{}", instructions, nodes.join("\n"))
     }
 
-    fn parse_instructions(s: &String) -> Vec<Instruction> {
-        s.chars()
-            .map(|s| Instruction::new(s.to_string().trim()))
-            .collect()
+    /// The instructions and the network, the latter as a list of nodes.
+    pub fn parse_input(input: &str) -> Result<(Vec<Instruction>, Vec<Node>), ParseError> {
+        let input = Field::new(input);
+        let blocks = input.blocks();
+        if blocks.len() != 2 {
+            return Err(input.error(format!("Expected instructions and nodes, found {} blocks", blocks.len())));
+        }
+        Ok((Day8::parse_instructions(blocks[0])?, Day8::parse_nodes(blocks[1])?))
     }
 
-    fn parse_nodes(s: &String) -> Vec<Node> {
-        s.split("\n")
-            .map(|s| {
-                let mut parts = s.split(" = ");
-                let id = parts.next().unwrap().trim().to_string();
-
-                let mut parts = parts.next().unwrap()
-                    .strip_prefix("(").unwrap()
-                    .strip_suffix(")").unwrap()
-                    .split(",");
-
-                let left = parts.next().unwrap().trim().to_string();
-                let right = parts.next().unwrap().trim().to_string();
-                Node::new(id, left, right)
+    pub fn parse_instructions(s: Field) -> Result<Vec<Instruction>, ParseError> {
+        s.text.char_indices()
+            .map(|(i, c)| match c {
+                'L' => Ok(Instruction::L),
+                'R' => Ok(Instruction::R),
+                _ => Err(s.slice(i..i + c.len_utf8()).error(format!("Invalid instruction '{}'", c))),
             })
             .collect()
     }
 
-    fn get_network(&self) -> Network {
-        let data = Day8::split_input(&self.input);
-        let nodes = Day8::parse_nodes(&data[1]);
-        Network::new(nodes)
+    /// Every left and right has to be a node of the network.
+    pub fn parse_nodes(s: Field) -> Result<Vec<Node>, ParseError> {
+        let pattern = Pattern::new("{id} = ({left}, {right})");
+        let nodes = s.lines().into_iter()
+            .map(|line| line.matches(&pattern))
+            .collect::<Result<Vec<_>, ParseError>>()?;
+        let ids: HashSet<&str> = nodes.iter().map(|node| node.get("id").text).collect();
+        for next in nodes.iter().flat_map(|node| [node.get("left"), node.get("right")]) {
+            if !ids.contains(next.text) {
+                return Err(next.error(format!("Unknown node '{}'", next.text)));
+            }
+        }
+        nodes.iter()
+            .map(|node| Ok(Node::new(node.parse("id")?, node.parse("left")?, node.parse("right")?)))
+            .collect()
     }
 
-    fn get_instructions(&self) -> Vec<Instruction> {
-        let data = Day8::split_input(&self.input);
-        Day8::parse_instructions(&data[0])
+    fn parsed_input(&self) -> (Vec<Instruction>, Vec<Node>) {
+        Day8::parse_input(&self.input).unwrap_or_else(|e| panic!("Invalid input: {}", e))
     }
-    fn get_journey(&self) -> Journey {
-        let data = Day8::split_input(&self.input);
-        let instructions = Day8::parse_instructions(&data[0]);
-        let nodes = Day8::parse_nodes(&data[1]);
 
-        let network = Network::new(nodes);
-        Journey::new(instructions, network)
+    pub fn get_network(&self) -> Network {
+        Network::new(self.parsed_input().1)
     }
-}
 
-impl Day for Day8 {
-    fn part1(&self) -> String {
-        let mut journey = self.get_journey();
-        while !journey.is_end() {
-            journey.travel();
-        }
-        journey.steps.to_string()
+    pub fn get_instructions(&self) -> Vec<Instruction> {
+        self.parsed_input().0
     }
 
-    fn part2(&self) -> String {
+    pub fn get_journey(&self) -> Journey {
+        let (instructions, nodes) = self.parsed_input();
+        Journey::new(instructions, Network::new(nodes))
+    }
+
+    /// Starts a journey at every node ending with `A`.
+    pub fn get_ghost_journeys(&self) -> Vec<Journey> {
         let network = self.get_network();
         let instructions = self.get_instructions();
-        let mut journeys: Vec<Journey> = network.nodes.clone().iter()
+        network.nodes.iter()
             .filter(|n| n.is_start())
             .map(|n| {
                 let mut journey = Journey::new(instructions.clone(), network.clone());
                 journey.set_start(n.id.clone());
                 journey
             })
-            .collect();
+            .collect()
+    }
+
+    /// Steps all journeys in lockstep until they all end at once, kept to cross-check part 2.
+    /// Polls for cancellation between steps as the number of iterations has no upper bound.
+    pub fn part2_lockstep(&self, ctx: &Context) -> Result<String, SolveError> {
+        let mut journeys = self.get_ghost_journeys();
+        if journeys.is_empty() {
+            return Err(SolveError::NoSolution("No node ends with A".to_string()));
+        }
 
-        // TODO: To many iterations, need a shortcut (gcd of each journey path?)
-        loop {
-            // println!("Iteration: {}", i);
+        ctx.progress.set_message(&format!("{} journeys", journeys.len()));
+        for i in 0u64.. {
+            ctx.cancel.check()?;
+            ctx.progress.inc(1);
+            trace!("Iteration: {}", i);
             let done = thread::scope(|s| {
                 let handles = journeys.iter_mut()
                     .map(|j| s.spawn(|| {
@@ -215,16 +258,94 @@ impl Day for Day8 {
             }
         }
 
-        journeys.iter()
+        Ok(journeys.iter()
             .map(|j| j.steps)
             .max()
             .unwrap()
-            .to_string()
+            .to_string())
+    }
+}
+
+impl Day for Day8 {
+    fn part1(&self) -> String {
+        self.part1_with(&Context::default()).unwrap_or_else(|e| panic!("Cannot reach the end: {}", e))
+    }
+
+    fn part2(&self) -> String {
+        self.part2_with(&Context::default()).unwrap_or_else(|e| panic!("Ghosts cannot escape: {}", e))
+    }
+
+    /// The journey repeats once it is back on a node at the same instruction, so an end node that is not
+    /// reached within nodes times instructions steps is never reached.
+    fn part1_with(&self, ctx: &Context) -> Result<String, SolveError> {
+        let mut journey = self.get_journey();
+        if journey.network.get_node(&journey.current).is_none() {
+            return Err(SolveError::NoSolution(format!("No node {}", journey.current)));
+        }
+        let limit = (journey.network.nodes.len() as u64).saturating_mul(journey.instructions.len() as u64);
+        while !journey.is_end() {
+            if journey.steps > limit {
+                return Err(SolveError::NoSolution("AAA never reaches a node ending with Z".to_string()));
+            }
+            if journey.steps.is_multiple_of(1_000_000) {
+                ctx.cancel.check()?;
+            }
+            journey.travel();
+        }
+        Ok(journey.steps.to_string())
+    }
+
+    /// Every ghost walks a finite state machine of node and instruction position, so it ends up
+    /// in a loop. The first step all of them are on an end node follows from the loop shapes.
+    /// Finding a loop may take up to nodes times instructions steps, so the ghosts poll for cancellation.
+    fn part2_with(&self, ctx: &Context) -> Result<String, SolveError> {
+        let network = self.get_network();
+        let instructions = self.get_instructions();
+        let starts: Vec<&Node> = network.nodes.iter().filter(|n| n.is_start()).collect();
+        if starts.is_empty() {
+            return Err(SolveError::NoSolution("No node ends with A".to_string()));
+        }
+
+        ctx.progress.set_total(starts.len() as u64);
+        let mut cycles = Vec::new();
+        for start in starts {
+            ctx.cancel.check()?;
+            let cycle = cycle::hashed(
+                (start.id.clone(), 0),
+                |(id, i)| {
+                    let node = network.get_node(id).unwrap();
+                    let next = match instructions[*i] {
+                        Instruction::L => &node.left,
+                        Instruction::R => &node.right,
+                    };
+                    (next.clone(), (i + 1) % instructions.len())
+                },
+                |(id, _)| id.ends_with('Z'));
+            debug!("Ghost loop of length {} after {} steps, ends at {:?}", cycle.length, cycle.tail, cycle.hits);
+            cycles.push(cycle);
+            ctx.progress.inc(1);
+        }
+
+        cycle::first_common_hit(&cycles)
+            .map(|step| step.to_string())
+            .ok_or_else(|| SolveError::NoSolution("Ghosts never end at the same time".to_string()))
+    }
+
+    fn alternatives(&self) -> Vec<Alternative<'_>> {
+        vec![Alternative::new(Part::Two, "lockstep", |ctx| self.part2_lockstep(ctx))]
+    }
+
+    fn validate(&self) -> Result<(), ParseError> {
+        Day8::parse_input(&self.input).map(|_| ())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::aoc::cancel::CancellationToken;
+    use crate::aoc::progress::Progress;
+    use crate::aoc::tools::prop::{assert_property, ensure_eq};
+
     use super::*;
 
     const INPUT: &str = r#"
@@ -277,4 +398,52 @@ mod tests {
         let day = Day8::new(INPUT_3.to_string());
         assert_eq!(day.part2(), "6");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_part2_cancelled() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let ctx = Context::new(token, Progress::disabled());
+        assert_eq!(Day8::new(INPUT_3.to_string()).part2_with(&ctx), Err(SolveError::Cancelled));
+    }
+
+    #[test]
+    fn test_no_solution() {
+        let ctx = Context::default();
+        let no_solution = |reason: &str| Err(SolveError::NoSolution(reason.to_string()));
+        assert_eq!(Day8::new(INPUT_3.to_string()).part1_with(&ctx), no_solution("No node AAA"));
+        let day = Day8::new("L\n\nAAA = (BBB, BBB)\nBBB = (AAA, AAA)\nZZZ = (ZZZ, ZZZ)".to_string());
+        assert_eq!(day.part1_with(&ctx), no_solution("AAA never reaches a node ending with Z"));
+
+        let day = Day8::new("L\n\nBBB = (BBB, BBB)".to_string());
+        assert_eq!(day.part2_with(&ctx), no_solution("No node ends with A"));
+        assert_eq!(day.part2_lockstep(&ctx), no_solution("No node ends with A"));
+        // The first ghost ends at odd steps, the second one at even steps
+        let ghosts = "L\n\n11A = (11Z, 11Z)\n11Z = (11A, 11A)\n22A = (22B, 22B)\n22B = (22Z, 22Z)\n22Z = (22B, 22B)";
+        let day = Day8::new(ghosts.to_string());
+        assert_eq!(day.part2_with(&ctx), no_solution("Ghosts never end at the same time"));
+    }
+
+    #[test]
+    fn test_part2_lockstep() {
+        let day = Day8::new(INPUT_3.to_string());
+        assert_eq!(day.part2_lockstep(&Context::default()), Ok("6".to_string()));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(Day8::new(INPUT.to_string()).validate(), Ok(()));
+        let error = Day8::new("LR\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)".to_string()).validate().unwrap_err();
+        assert_eq!(error.to_string(), "line 3, column 8: Unknown node 'BBB'");
+        let error = Day8::new("LRX\n\nAAA = (AAA, AAA)".to_string()).validate().unwrap_err();
+        assert_eq!(error.to_string(), "line 1, column 3: Invalid instruction 'X'");
+    }
+
+    #[test]
+    fn test_loops_match_lockstep() {
+        assert_property("ghosts end together", Day8::generate, |input| {
+            let day = Day8::new(input.clone());
+            ensure_eq(Ok(day.part2()), day.part2_lockstep(&Context::default()), "steps")
+        });
+    }
+}
@@ -1,7 +1,10 @@
-use std::ops::{Add, Sub};
-
-use crate::aoc::Day;
-use crate::aoc::tools::read_lines;
+use crate::aoc::{Context, Day};
+use crate::aoc::crosscheck::Alternative;
+use crate::aoc::runner::{Part, SolveError};
+use crate::aoc::tools::bigint::BigInt;
+use crate::aoc::tools::checked::{checked_sum, Checked, Overflow};
+use crate::aoc::tools::parse::{parse_lines, ParseError};
+use crate::aoc::tools::prop::Gen;
 
 pub trait VecExt<T>: AsMut<Vec<T>> {
     fn prepend(&mut self, v: T) {
@@ -11,26 +14,27 @@ pub trait VecExt<T>: AsMut<Vec<T>> {
 
 impl<T> VecExt<T> for Vec<T> {}
 
-struct Dataset {
-    histories: Vec<History>,
+pub struct Dataset {
+    pub histories: Vec<History>,
 }
 
 impl Dataset {
-    fn new(histories: Vec<History>) -> Self {
+    pub fn new(histories: Vec<History>) -> Self {
         Self { histories }
     }
 }
 
-struct History {
-    numbers: Vec<i64>,
+pub struct History {
+    pub numbers: Vec<i64>,
 }
 
 impl History {
-    fn new(numbers: Vec<i64>) -> Self {
+    pub fn new(numbers: Vec<i64>) -> Self {
         Self { numbers }
     }
 
-    fn extrapolate(&mut self, forward: bool) -> i64 {
+    /// Fails if a difference or the extrapolated value does not fit an `i64`.
+    pub fn extrapolate(&mut self, forward: bool) -> Result<i64, Overflow> {
         // Create initial state
         let mut stages = Vec::new();
 
@@ -40,7 +44,7 @@ impl History {
         loop {
             let mut stage = Vec::new();
             for i in 0..previous.len() - 1 {
-                stage.push(previous[i + 1] - previous[i]);
+                stage.push(previous[i + 1].try_sub(previous[i])?);
             }
             previous = stage.clone();
             stages.push(stage.clone());
@@ -62,12 +66,12 @@ impl History {
                 true => {
                     let lower_last = stages.get(i + 1).unwrap().last().unwrap();
                     let last = stages.get(i).unwrap().last().unwrap();
-                    last.add(lower_last)
+                    last.try_add(*lower_last)?
                 }
                 false => {
                     let lower_first = stages.get(i + 1).unwrap().first().unwrap();
                     let first = stages.get(i).unwrap().first().unwrap();
-                    first.sub(lower_first)
+                    first.try_sub(*lower_first)?
                 }
             };
             if forward {
@@ -78,17 +82,28 @@ impl History {
         }
 
         if forward {
-            stages
-                .first_mut().unwrap()
-                .last_mut().unwrap()
-                .clone()
+            Ok(*stages
+                .first().unwrap()
+                .last().unwrap())
         } else {
-            stages
-                .first_mut().unwrap()
-                .first_mut().unwrap()
-                .clone()
+            Ok(*stages
+                .first().unwrap()
+                .first().unwrap())
         }
     }
+
+    /// Exact extrapolation for histories whose values outgrow `i64`.
+    pub fn extrapolate_exact(&self, forward: bool) -> BigInt {
+        let mut row: Vec<BigInt> = self.numbers.iter().map(|n| BigInt::from(*n)).collect();
+        // The value at the extrapolated end of each stage
+        let mut edges = Vec::new();
+        while row.iter().any(|v| !v.is_zero()) {
+            edges.push(if forward { row.last() } else { row.first() }.unwrap().clone());
+            row = row.windows(2).map(|w| &w[1] - &w[0]).collect();
+        }
+        edges.into_iter().rev()
+            .fold(BigInt::zero(), |lower, edge| if forward { &edge + &lower } else { &edge - &lower })
+    }
 }
 
 pub struct Day9 {
@@ -96,45 +111,82 @@ pub struct Day9 {
 }
 
 impl Day9 {
-    fn read_dataset(&self) -> Dataset {
-        Dataset::new(
-            read_lines(&self.input).iter()
-                .map(|l| History::new(l.split_whitespace()
-                    .map(|s| s.parse::<i64>().unwrap())
-                    .collect()))
-                .collect::<Vec<History>>())
+    /// One history of whitespace separated values per line.
+    pub fn parse_dataset(input: &str) -> Result<Dataset, ParseError> {
+        parse_lines(input, |line| line.list().map(History::new)).map(Dataset::new)
+    }
+
+    pub fn read_dataset(&self) -> Dataset {
+        Day9::parse_dataset(&self.input).unwrap_or_else(|e| panic!("Invalid dataset: {}", e))
     }
 
     pub fn new(input: String) -> Day9 {
         Day9 { input }
     }
+
+    /// Random histories, each the values of a polynomial with small coefficients at consecutive points.
+    /// There are always enough values for the differences to reach zero.
+    pub fn generate(g: &mut Gen) -> String {
+        let histories = g.vec(g.scaled(), |g| {
+            let coefficients = g.vec(1..=5, |g| g.range(-9..=9));
+            let start = g.range(-5..=5);
+            let len = coefficients.len() + g.usize(1..=7);
+            (start..start + len as i64)
+                .map(|x| coefficients.iter().rev().fold(0, |value, c| value * x + c).to_string())
+                .collect::<Vec<String>>()
+                .join(" ")
+        });
+        histories.join("\n")
+    }
 }
 
 impl Day for Day9 {
     fn part1(&self) -> String {
-        let mut dataset = self.read_dataset();
-        let result: i64 = dataset.histories.iter_mut()
-            .map(|h| {
-                h.extrapolate(true)
-            })
-            .sum();
-        result.to_string()
+        self.part1_with(&Context::default()).unwrap_or_else(|e| panic!("Extrapolation does not fit: {}", e))
     }
 
     fn part2(&self) -> String {
+        self.part2_with(&Context::default()).unwrap_or_else(|e| panic!("Extrapolation does not fit: {}", e))
+    }
+
+    fn part1_with(&self, _ctx: &Context) -> Result<String, SolveError> {
+        let mut dataset = self.read_dataset();
+        let result = dataset.histories.iter_mut()
+            .map(|h| h.extrapolate(true))
+            .collect::<Result<Vec<i64>, Overflow>>()
+            .and_then(checked_sum)?;
+        Ok(result.to_string())
+    }
+
+    fn part2_with(&self, _ctx: &Context) -> Result<String, SolveError> {
         let mut dataset = self.read_dataset();
-        let result: i64 = dataset.histories.iter_mut()
-            .map(|h| {
-                h.extrapolate(false)
-            })
-            .sum();
-        result.to_string()
+        let result = dataset.histories.iter_mut()
+            .map(|h| h.extrapolate(false))
+            .collect::<Result<Vec<i64>, Overflow>>()
+            .and_then(checked_sum)?;
+        Ok(result.to_string())
+    }
+
+    fn alternatives(&self) -> Vec<Alternative<'_>> {
+        let exact = |forward| self.read_dataset().histories.iter()
+            .map(|h| h.extrapolate_exact(forward))
+            .sum::<BigInt>()
+            .to_string();
+        vec![
+            Alternative::new(Part::One, "exact", move |_| Ok(exact(true))),
+            Alternative::new(Part::Two, "exact", move |_| Ok(exact(false))),
+        ]
+    }
+
+    fn validate(&self) -> Result<(), ParseError> {
+        Day9::parse_dataset(&self.input).map(|_| ())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::aoc::Day;
+    use crate::aoc::tools::prop::{assert_property, ensure_eq};
 
     use super::*;
 
@@ -163,4 +215,43 @@ mod tests {
         let day = Day9::new(INPUT_2.to_string());
         assert_eq!(day.part2(), "5".to_string());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_extrapolate_overflow() {
+        let mut history = History::new(vec![i64::MAX - 1, i64::MAX]);
+        let error = history.extrapolate(true).unwrap_err();
+        assert_eq!(error.to_string(), "9223372036854775807 + 1 overflows i64");
+        assert_eq!(history.extrapolate_exact(true).to_string(), "9223372036854775808");
+        assert_eq!(history.extrapolate(false), Ok(i64::MAX - 2));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(Day9::new(INPUT.to_string()).validate(), Ok(()));
+        let error = Day9::new("1 2\n3 99999999999999999999".to_string()).validate().unwrap_err();
+        assert_eq!(error.to_string(), "line 2, column 3: Expected i64, found '99999999999999999999'");
+    }
+
+    #[test]
+    fn test_extrapolate_exact() {
+        for history in Day9::new(INPUT.to_string()).read_dataset().histories.iter_mut() {
+            for forward in [true, false] {
+                assert_eq!(history.extrapolate_exact(forward).to_i64(), history.extrapolate(forward).ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_extrapolate_matches_exact() {
+        assert_property("extrapolation", Day9::generate, |input| {
+            for history in Day9::new(input.clone()).read_dataset().histories.iter_mut() {
+                for forward in [true, false] {
+                    let value = history.extrapolate(forward).map_err(|e| e.to_string())?;
+                    ensure_eq(BigInt::from(value), history.extrapolate_exact(forward),
+                              &format!("extrapolations of {:?}", history.numbers))?;
+                }
+            }
+            Ok(())
+        });
+    }
+}
@@ -0,0 +1,16 @@
+//! Solutions for the Advent of Code 2023.
+//!
+//! Every day exposes its solver implementing [`aoc::Day`] together with the types used to parse
+//! and solve the puzzle, so the solutions can be reused from other binaries and tests.
+pub mod aoc;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod day10;
+pub mod day11;
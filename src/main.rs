@@ -1,20 +1,43 @@
-use clap::{Arg, Command};
-
-mod aoc;
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
-mod day10;
-mod day11;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::{Arg, ArgAction, Command};
+
+use aoc2023::aoc;
+use aoc2023::aoc::{Context, crosscheck};
+use aoc2023::aoc::logging::{Logger, verbosity};
+use aoc2023::aoc::progress::{self, ProgressMode};
+use aoc2023::aoc::runner;
+use aoc2023::aoc::tools::prop::{Config, Gen};
+use aoc2023::aoc::viz;
+use aoc2023::aoc::viz::ImageFormat;
+use aoc2023::aoc::viz::record::Recorder;
+
+/// The day argument shared by all subcommands.
+fn day_arg(help: &'static str) -> Arg {
+    Arg::new("day")
+        .short('d')
+        .long("day")
+        .help(help)
+        .required(true)
+        .value_parser(clap::value_parser!(u8))
+}
+
+/// Writes a file, creating its directory if needed.
+/// Prints the error and exits on failure, the answers have been printed already.
+fn write_or_exit(path: &Path, contents: &[u8]) {
+    let written = match path.parent() {
+        Some(dir) => std::fs::create_dir_all(dir).and_then(|_| std::fs::write(path, contents)),
+        None => std::fs::write(path, contents),
+    };
+    if let Err(e) = written {
+        eprintln!("Cannot write {}: {}", path.display(), e);
+        std::process::exit(1);
+    }
+}
 
 /// A cli interface to run the solutions for the AoC 2023.
-/// The input files are downloaded automatically if they do not exist.
+/// With the `fetch` feature the input files are downloaded automatically if they do not exist.
 /// The session cookie is passed as an environment variable.
 fn main() {
     let app = Command::new("AoC 2023")
@@ -22,25 +45,269 @@ fn main() {
         .author("Marius Gassen")
         .about("Solutions for the Advent of Code 2023");
 
-    let matches = app
+    let app = app
+        .arg(Arg::new("verbose")
+            .short('v')
+            .long("verbose")
+            .help("Increases log output, repeat for more detail")
+            .action(ArgAction::Count)
+            .global(true))
+        .arg(Arg::new("quiet")
+            .short('q')
+            .long("quiet")
+            .help("Decreases log output, repeat to silence errors as well")
+            .action(ArgAction::Count)
+            .global(true))
+        .arg(Arg::new("log")
+            .long("log")
+            .help("Log levels per module, e.g. day8=trace,aoc::runner=debug")
+            .global(true))
         .subcommand(Command::new("run")
             .about("Runs the solution for a given day")
-            .arg(Arg::new("day")
-                .short('d')
-                .long("day")
-                .help("The day to run the solution for")
-                .required(true)
-                .value_parser(clap::value_parser!(u8))))
-        .get_matches();
+            .arg(day_arg("The day to run the solution for"))
+            .arg(Arg::new("timeout")
+                .short('t')
+                .long("timeout")
+                .help("Maximum number of seconds each part may run before it is cancelled")
+                .value_parser(clap::value_parser!(u64)))
+            .arg(Arg::new("no-progress")
+                .long("no-progress")
+                .help("Disables progress reporting, e.g. for timing runs")
+                .action(ArgAction::SetTrue))
+            .arg(Arg::new("visualize")
+                .long("visualize")
+                .help("Draws the puzzle after solving it, coloured when stdout is a terminal")
+                .action(ArgAction::SetTrue))
+            .arg(Arg::new("export")
+                .long("export")
+                .help("Writes the drawing of the puzzle to a .png, .ppm or .svg file")
+                .value_parser(clap::value_parser!(PathBuf)))
+            .arg(Arg::new("frames")
+                .long("frames")
+                .help("Writes the animation of the puzzle as PNG frames into a directory")
+                .value_parser(clap::value_parser!(PathBuf)))
+            .arg(Arg::new("frame-step")
+                .long("frame-step")
+                .help("Keeps only every n-th step of the animation")
+                .default_value("1")
+                .value_parser(clap::value_parser!(u64).range(1..)))
+            .arg(Arg::new("cell-size")
+                .long("cell-size")
+                .help("Size of a grid cell in exported images, in pixels")
+                .default_value("8")
+                .value_parser(clap::value_parser!(u64).range(1..))))
+        .subcommand(Command::new("crosscheck")
+            .about("Compares the alternative solvers of a day with the main ones on generated inputs")
+            .arg(day_arg("The day to cross-check"))
+            .arg(Arg::new("cases")
+                .long("cases")
+                .help("Number of generated inputs")
+                .default_value("100")
+                .value_parser(clap::value_parser!(usize)))
+            .arg(Arg::new("seed")
+                .long("seed")
+                .help("Seed of the generated inputs, defaults to AOC_PROP_SEED or a fixed seed")
+                .value_parser(clap::value_parser!(u64)))
+            .arg(Arg::new("input")
+                .long("input")
+                .help("Checks the puzzle input as well, brute force solvers may take very long on it")
+                .action(ArgAction::SetTrue))
+            .arg(Arg::new("timeout")
+                .short('t')
+                .long("timeout")
+                .help("Maximum number of seconds the check of the puzzle input may run before it is cancelled")
+                .value_parser(clap::value_parser!(u64))))
+        .subcommand(Command::new("gen")
+            .about("Prints a random input for a given day, the same size and seed always give the same input")
+            .arg(day_arg("The day to generate an input for"))
+            .arg(Arg::new("size")
+                .long("size")
+                .help("How large the input is, e.g. the number of lines or the width of a grid")
+                .default_value("10")
+                .value_parser(clap::value_parser!(u64).range(1..)))
+            .arg(Arg::new("seed")
+                .long("seed")
+                .help("Seed of the random choices")
+                .default_value("0")
+                .value_parser(clap::value_parser!(u64)))
+            .arg(Arg::new("density")
+                .long("density")
+                .help("Share of filled grid cells in percent, e.g. galaxies for day 11, random by default")
+                .value_parser(clap::value_parser!(u64).range(0..=100))));
+
+    #[cfg(feature = "fetch")]
+    let app = app
+        .subcommand(Command::new("fetch")
+            .about("Downloads the input for a given day if it does not exist yet")
+            .arg(day_arg("The day to download the input for")));
+
+    let matches = app.get_matches();
+
+    let quiet = matches.get_count("quiet");
+    let level = verbosity(matches.get_count("verbose"), quiet);
+    // Progress log lines are shown by default, later directives from --log take precedence
+    let mut filters = match quiet {
+        0 => format!("{}=info,", progress::LOG_TARGET),
+        _ => String::new(),
+    };
+    filters.push_str(matches.get_one::<String>("log").map(|s| s.as_str()).unwrap_or(""));
+    match Logger::new(level).parse_filters(&filters) {
+        Ok(logger) => logger.init().expect("Logger is only initialised once"),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    }
 
     match matches.subcommand() {
         Some(("run", subcommand_matches)) => {
+            let day = subcommand_matches
+                .get_one("day")
+                .unwrap();
+            let timeout = subcommand_matches
+                .get_one::<u64>("timeout")
+                .map(|s| Duration::from_secs(*s));
+            let progress = match subcommand_matches.get_flag("no-progress") {
+                true => ProgressMode::Hidden,
+                false => ProgressMode::detect(),
+            };
+
+            let export = subcommand_matches.get_one::<PathBuf>("export").map(|path| {
+                let format = ImageFormat::from_path(path).unwrap_or_else(|| {
+                    eprintln!("Unknown image format of {}, use .png, .ppm or .svg", path.display());
+                    std::process::exit(2);
+                });
+                (path, format)
+            });
+            let cell = *subcommand_matches.get_one::<u64>("cell-size").unwrap() as usize;
+
+            let solution = aoc::run_solution(*day, timeout, progress).unwrap();
+            println!("Solution for day {}\n- Part 1: {}\n- Part 2: {}", day, solution.0, solution.1);
+
+            if subcommand_matches.get_flag("visualize") || export.is_some() {
+                match aoc::visualize(*day).unwrap() {
+                    Some(canvas) => {
+                        if subcommand_matches.get_flag("visualize") {
+                            print!("{}", canvas.render(viz::colour_enabled()));
+                        }
+                        if let Some((path, format)) = export {
+                            write_or_exit(path, &canvas.encode(format, cell));
+                            println!("Drawing written to {}", path.display());
+                        }
+                    }
+                    None => println!("No visualisation for day {}", day),
+                }
+            }
+
+            if let Some(dir) = subcommand_matches.get_one::<PathBuf>("frames") {
+                let step = *subcommand_matches.get_one::<u64>("frame-step").unwrap() as usize;
+                let mut recorder = Recorder::every(step);
+                aoc::animate(*day, &mut recorder).unwrap();
+                if recorder.is_empty() {
+                    println!("No animation for day {}", day);
+                } else {
+                    match recorder.write_frames(dir, ImageFormat::Png, cell) {
+                        Ok(files) => println!("{} frames written to {}", files.len(), dir.display()),
+                        Err(e) => {
+                            eprintln!("Cannot write frames to {}: {}", dir.display(), e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+            }
+        }
+        Some(("crosscheck", subcommand_matches)) => {
+            let day = *subcommand_matches.get_one::<u8>("day").unwrap();
+            let Some(solver) = aoc::get_day(day, String::new()) else {
+                eprintln!("Day {} not implemented yet", day);
+                std::process::exit(2);
+            };
+            let alternatives = solver.alternatives().len();
+            if alternatives == 0 {
+                println!("No alternative solvers for day {}", day);
+                return;
+            }
+            println!("Cross-checking {} alternative solvers for day {}", alternatives, day);
+
+            let mut agree = true;
+            match crosscheck::crosscheck_examples(day, &Context::default()) {
+                Ok(disagreements) => {
+                    for (example, disagreement) in &disagreements {
+                        println!("- Example {}: {}", example, disagreement);
+                    }
+                    if disagreements.is_empty() {
+                        println!("- Examples: all solvers agree on {} examples", aoc::get_examples(day).len());
+                    }
+                    agree &= disagreements.is_empty();
+                }
+                Err(e) => {
+                    println!("- Examples: {}", e);
+                    agree = false;
+                }
+            }
+
+            if subcommand_matches.get_flag("input") {
+                let solver = aoc::get_day(day, aoc::read_input(day).unwrap()).unwrap();
+                let timeout = subcommand_matches
+                    .get_one::<u64>("timeout")
+                    .map(|s| Duration::from_secs(*s));
+                let check = move |ctx: &Context| crosscheck::crosscheck(solver.as_ref(), ctx);
+                match runner::supervise("Puzzle input", timeout, ProgressMode::detect(), check) {
+                    Ok((disagreements, _)) => {
+                        for disagreement in &disagreements {
+                            println!("- Puzzle input: {}", disagreement);
+                        }
+                        if disagreements.is_empty() {
+                            println!("- Puzzle input: all solvers agree");
+                        }
+                        agree &= disagreements.is_empty();
+                    }
+                    Err(status) => {
+                        println!("- Puzzle input: {}", status);
+                        agree = false;
+                    }
+                }
+            }
+
+            let mut config = Config::default().cases(*subcommand_matches.get_one::<usize>("cases").unwrap());
+            if let Some(seed) = subcommand_matches.get_one::<u64>("seed") {
+                config.seed = *seed;
+            }
+            match crosscheck::crosscheck_generated(day, &config) {
+                Ok(cases) => println!("- Generated inputs: all solvers agree on {} inputs of seed {}", cases, config.seed),
+                Err(failure) => {
+                    println!("- Generated inputs: {}", failure.message);
+                    println!("Smallest disagreeing input, case {} of seed {} shrunk {} times:\n{}",
+                             failure.case, failure.seed, failure.shrinks, failure.value);
+                    agree = false;
+                }
+            }
+            if !agree {
+                std::process::exit(1);
+            }
+        }
+        Some(("gen", subcommand_matches)) => {
+            let day = *subcommand_matches.get_one::<u8>("day").unwrap();
+            let size = *subcommand_matches.get_one::<u64>("size").unwrap() as usize;
+            let seed = *subcommand_matches.get_one::<u64>("seed").unwrap();
+            let Some(generate) = aoc::get_generator(day) else {
+                eprintln!("Day {} not implemented yet", day);
+                std::process::exit(2);
+            };
+            let mut g = Gen::new(seed, size);
+            if let Some(density) = subcommand_matches.get_one::<u64>("density") {
+                g = g.with_density(*density);
+            }
+            println!("{}", generate(&mut g));
+        }
+        #[cfg(feature = "fetch")]
+        Some(("fetch", subcommand_matches)) => {
             let day = subcommand_matches
                 .get_one("day")
                 .unwrap();
 
-            let solution = aoc::run_solution(*day).unwrap();
-            println!("Solution for day {}\n- Part 1: {}\n- Part 2: {}", day, solution.0, solution.1)
+            aoc::fetch::get_day_input(*day).unwrap();
+            println!("Input for day {} is available", day)
         }
         _ => {
             println!("Missing or invalid subcommand");
@@ -0,0 +1,24 @@
+use std::path::Path;
+
+use aoc2023::aoc::fuzz::{fuzz, load_corpus};
+use aoc2023::aoc::tools::prop::Config;
+
+/// `AOC_FUZZ_CASES` runs more cases per day, e.g. for a longer local session.
+fn config() -> Config {
+    let cases = std::env::var("AOC_FUZZ_CASES").ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(300);
+    Config::default().cases(cases).max_size(30)
+}
+
+#[test]
+fn test_days_never_panic() {
+    for day in 1..=11 {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("fuzz/corpus/day{}", day));
+        let corpus = load_corpus(&dir).unwrap_or_else(|e| panic!("Cannot read {}: {}", dir.display(), e));
+        assert!(!corpus.is_empty(), "Corpus of day {} is empty", day);
+        if let Err(failure) = fuzz(day, &corpus, &config()) {
+            panic!("Day {} fails on a fuzzed input. {}", day, failure);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use aoc2023::aoc::{Context, get_day, get_generator};
+use aoc2023::aoc::crosscheck::{crosscheck, crosscheck_examples, crosscheck_generated};
+use aoc2023::aoc::tools::parse::Field;
+use aoc2023::aoc::tools::Point;
+use aoc2023::aoc::progress::ProgressMode;
+use aoc2023::aoc::runner::{Part, run_part, Status};
+use aoc2023::aoc::tools::prop::{Config, Gen};
+use aoc2023::day2::{Cubes, Day2};
+use aoc2023::day5::{Almanac, Location, Seed};
+use aoc2023::day11::Universe;
+
+const ALMANAC: &str = r#"
+    seeds: 79 14 55 13
+
+    seed-to-soil map:
+    50 98 2
+    52 50 48
+
+    soil-to-fertilizer map:
+    0 15 37
+    37 52 2
+    39 0 15
+
+    fertilizer-to-water map:
+    49 53 8
+    0 11 42
+    42 0 7
+    57 7 4
+
+    water-to-light map:
+    88 18 7
+    18 25 70
+
+    light-to-temperature map:
+    45 77 23
+    81 45 19
+    68 64 13
+
+    temperature-to-humidity map:
+    0 69 1
+    1 0 69
+
+    humidity-to-location map:
+    60 56 37
+    56 93 4"#;
+
+#[test]
+fn test_get_day() {
+    let day = get_day(9, "0 3 6 9 12 15".to_string()).unwrap();
+    assert_eq!(day.part1(), "18");
+    assert!(get_day(25, String::new()).is_none());
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_run() {
+    let day = get_day(9, "0 3 6 9 12 15".to_string()).unwrap();
+    assert_eq!(day.run(), ("18".to_string(), "-3".to_string()));
+}
+
+#[test]
+fn test_run_part() {
+    let day = get_day(6, "Time: 7 15 30\nDistance: 9 40 200".to_string()).unwrap();
+    let status = run_part(day, Part::One, Some(Duration::from_secs(10)), ProgressMode::Hidden);
+    assert!(matches!(status, Status::Solved(ref answer, _) if answer == "288"));
+}
+
+#[test]
+fn test_run_part_overflow() {
+    let day = get_day(2, "Game 1: 5000 red, 5000 green, 5000 blue".to_string()).unwrap();
+    let status = run_part(day, Part::Two, None, ProgressMode::Hidden);
+    assert_eq!(status.to_string(), "overflowed: 25000000 * 5000 overflows u32");
+}
+
+#[test]
+fn test_run_part_no_solution() {
+    let day = get_day(10, "...\n.S-".to_string()).unwrap();
+    let status = run_part(day, Part::One, None, ProgressMode::Hidden);
+    assert_eq!(status.to_string(), "no solution: The start is not on a loop");
+}
+
+#[test]
+fn test_day2_api() {
+    let game = Day2::parse_game(Field::new("Game 7: 3 blue, 4 red; 1 red, 2 green, 6 blue")).unwrap();
+    assert_eq!(game.id, 7);
+    assert_eq!(game.min_bag().power(), 4 * 2 * 6);
+    assert!(Day2::possible(&Cubes::new(12, 13, 14), &game));
+
+    let error = Day2::parse_game(Field::new("Game 7: 3 blue, 4 pink")).unwrap_err();
+    assert_eq!((error.line, error.column), (1, 19));
+    assert_eq!(error.message, "Invalid color pink");
+}
+
+#[test]
+fn test_day5_api() {
+    let almanac = Almanac::from_str(ALMANAC).unwrap();
+    assert_eq!(almanac.seed_to_location(&Seed(79)), Location(82));
+    assert_eq!(almanac.seed_to_location(&Seed(13)), Location(35));
+}
+
+#[test]
+fn test_day11_api() {
+    let mut universe = Universe::new("#..\n...\n..#");
+    universe.expand_by(2);
+    assert_eq!(universe.galaxies(), vec![Point::new(0, 0), Point::new(2, 2)]);
+    assert_eq!(universe.distance(Point::new(0, 0), Point::new(2, 2)), 6);
+}
+
+#[test]
+fn test_crosscheck() {
+    for (day, input) in [(5, ALMANAC), (6, "Time: 7 15 30\nDistance: 9 40 200"), (9, "0 3 6 9 12 15\n1 3 6 10 15 21")] {
+        let solver = get_day(day, input.to_string()).unwrap();
+        assert!(!solver.alternatives().is_empty());
+        assert_eq!(crosscheck(solver.as_ref(), &Context::default()), Ok(vec![]), "day {}", day);
+    }
+    for day in 1..=11 {
+        assert_eq!(crosscheck_examples(day, &Context::default()), Ok(vec![]), "day {}", day);
+        if let Err(failure) = crosscheck_generated(day, &Config::default().cases(30)) {
+            panic!("Solvers of day {} disagree. {}", day, failure);
+        }
+    }
+}
+
+#[test]
+fn test_generated_inputs() {
+    for day in 1..=11 {
+        let generate = get_generator(day).unwrap();
+        let input = generate(&mut Gen::new(7, 8));
+        assert_eq!(generate(&mut Gen::new(7, 8)), input, "day {} is not deterministic", day);
+        assert!(generate(&mut Gen::new(7, 80)).len() > input.len(), "day {} does not grow", day);
+
+        let solver = get_day(day, input).unwrap();
+        solver.part1();
+        solver.part2();
+    }
+    assert!(get_generator(25).is_none());
+}
+
+#[test]
+fn test_large_generated_inputs() {
+    for day in 1..=10 {
+        let solver = get_day(day, get_generator(day).unwrap()(&mut Gen::new(3, 200))).unwrap();
+        solver.part1();
+        solver.part2();
+    }
+    // The kerned race of day 6 has to fit a u64 at any size
+    for (seed, size) in [(3, 1000), (4, 1000), (5, 10_000), (6, 1_000_000)] {
+        let solver = get_day(6, get_generator(6).unwrap()(&mut Gen::new(seed, size))).unwrap();
+        assert_eq!(crosscheck(solver.as_ref(), &Context::default()), Ok(vec![]), "seed {} size {}", seed, size);
+    }
+}
+
+#[test]
+fn test_parse_large_generated_inputs() {
+    // More than 10k lines each, the parsers have to track positions in linear time
+    for day in [2, 4, 5, 7, 9] {
+        let input = get_generator(day).unwrap()(&mut Gen::new(3, 20_000));
+        assert!(input.lines().count() > 10_000, "day {}", day);
+        assert_eq!(get_day(day, input).unwrap().validate(), Ok(()), "day {}", day);
+    }
+}